@@ -0,0 +1,66 @@
+use std;
+
+use failure;
+
+use zicsv;
+
+use print_err;
+
+/// Read blocked IPv4 addresses and networks, fail if list contains errors.
+///
+/// Errors are printed as they occur, so all of them are reported at once.
+pub fn read_ipv4_addresses(mut reader: Box<zicsv::GenericReader>) -> Result<zicsv::Addresses, failure::Error> {
+    let mut n_errors = 0usize;
+    let mut ipv4_addresses = zicsv::Addresses::new();
+
+    for record in reader.iter() {
+        match record {
+            Ok(record) => ipv4_addresses.extend(record.addresses.into_iter().filter(|address| match *address {
+                zicsv::Address::IPv4(_) | zicsv::Address::IPv4Network(_) => true,
+                _ => false,
+            })),
+
+            Err(error) => {
+                n_errors += 1;
+                print_err::print_error(&error);
+            },
+        }
+    }
+
+    ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
+    Ok(ipv4_addresses)
+}
+
+pub fn aggregate<StreamWriter>(
+    max_prefixes: Option<usize>,
+    print_stats: bool,
+    reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let ipv4_addresses = read_ipv4_addresses(reader)?;
+
+    if let Some(max_prefixes) = max_prefixes {
+        let aggregation = zicsv::aggregate_ipv4_lossy(&ipv4_addresses, max_prefixes);
+        for network in &aggregation.networks {
+            writeln!(writer, "{}", zicsv::Address::IPv4Network(*network))?;
+        }
+
+        if print_stats {
+            eprintln!(
+                "Networks: {}, blocked addresses: {}, over-blocked addresses: {}",
+                aggregation.networks.len(),
+                aggregation.n_blocked,
+                aggregation.n_overblocked
+            );
+        }
+    } else {
+        for network in zicsv::aggregate_ipv4(&ipv4_addresses) {
+            writeln!(writer, "{}", zicsv::Address::IPv4Network(network))?;
+        }
+    }
+
+    Ok(())
+}
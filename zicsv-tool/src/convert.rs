@@ -0,0 +1,494 @@
+use std;
+
+use failure;
+use serde;
+use serde_json;
+
+use zicsv;
+
+use export;
+use print_err;
+use serialize;
+
+pub struct RecordsSerializer<'a> {
+    n_errors: usize,
+    records: Box<Iterator<Item = Result<zicsv::Record, failure::Error>> + 'a>,
+}
+
+mod serialize_records {
+    use super::*;
+
+    pub fn serialize<'a, S>(value: &std::cell::RefCell<RecordsSerializer<'a>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use std::ops::DerefMut;
+
+        use serde::ser::SerializeSeq;
+
+        let mut records_serializer = value.borrow_mut();
+        let &mut RecordsSerializer {
+            ref mut n_errors,
+            ref mut records,
+        } = records_serializer.deref_mut();
+
+        let mut seq = serializer.serialize_seq(None)?;
+        for record in records {
+            match record {
+                Ok(record) => seq.serialize_element(&record)?,
+                Err(error) => {
+                    *n_errors += 1;
+                    print_err::print_error(&error);
+                },
+            }
+        }
+        seq.end()
+    }
+}
+
+#[derive(Serialize)]
+struct List<'a> {
+    updated: zicsv::DateTime,
+    #[serde(with = "serialize_records")]
+    records: std::cell::RefCell<RecordsSerializer<'a>>,
+}
+
+/// Write the whole list in one of machine-readable output formats.
+pub fn convert<StreamWriter>(
+    output_format: &super::OutputFormat,
+    mut reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let updated = *reader.get_timestamp();
+    let records = reader.iter();
+
+    let list = List {
+        updated,
+        records: std::cell::RefCell::new(RecordsSerializer { n_errors: 0, records }),
+    };
+
+    serialize::serialize(output_format, &list, writer)?;
+
+    let n_errors = list.records.borrow().n_errors;
+    ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct Organization<'a> {
+    id: usize,
+    name: &'a str,
+}
+
+#[derive(Serialize)]
+struct Document<'a> {
+    id: usize,
+    /// Id of organization.
+    organization: usize,
+    document_id: &'a str,
+    document_date: &'a zicsv::Date,
+}
+
+#[derive(Serialize)]
+struct NormalizedAddress<'a> {
+    /// Id of document.
+    document: usize,
+    address: &'a zicsv::Address,
+}
+
+/// List with organizations and documents stored only once and referenced by ids.
+#[derive(Serialize)]
+struct NormalizedList<'a> {
+    updated: zicsv::DateTime,
+    organizations: Vec<Organization<'a>>,
+    documents: Vec<Document<'a>>,
+    addresses: Vec<NormalizedAddress<'a>>,
+}
+
+impl<'a> NormalizedList<'a> {
+    fn new(updated: zicsv::DateTime, records: &'a [zicsv::Record]) -> Self {
+        let mut organization_ids = std::collections::HashMap::new();
+        let mut document_ids = std::collections::HashMap::new();
+        let mut list = Self {
+            updated,
+            organizations: Vec::new(),
+            documents: Vec::new(),
+            addresses: Vec::new(),
+        };
+
+        for record in records {
+            let organizations = &mut list.organizations;
+            let organization = *organization_ids
+                .entry(record.organization.as_str())
+                .or_insert_with(|| {
+                    let id = organizations.len() + 1;
+                    organizations.push(Organization {
+                        id,
+                        name: &record.organization,
+                    });
+                    id
+                });
+
+            // Records with the same document are merged.
+            let documents = &mut list.documents;
+            let document = *document_ids
+                .entry((organization, record.document_id.as_str(), record.document_date))
+                .or_insert_with(|| {
+                    let id = documents.len() + 1;
+                    documents.push(Document {
+                        id,
+                        organization,
+                        document_id: &record.document_id,
+                        document_date: &record.document_date,
+                    });
+                    id
+                });
+
+            list.addresses.extend(
+                record
+                    .addresses
+                    .iter()
+                    .map(|address| NormalizedAddress { document, address }),
+            );
+        }
+
+        list
+    }
+}
+
+/// Write list with "organizations", "documents" and "addresses" cross-referenced by ids starting from 1.
+///
+/// Unlike `convert()`, the whole list is read into memory.
+pub fn convert_normalized<StreamWriter>(
+    output_format: &super::OutputFormat,
+    mut reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let mut n_errors = 0usize;
+    let mut records = Vec::new();
+    for record in reader.iter() {
+        match record {
+            Ok(record) => records.push(record),
+
+            Err(error) => {
+                n_errors += 1;
+                print_err::print_error(&error);
+            },
+        }
+    }
+    ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
+
+    serialize::serialize(
+        output_format,
+        &NormalizedList::new(*reader.get_timestamp(), &records),
+        writer,
+    )
+}
+
+/// Options of newline-delimited JSON output.
+pub struct NdjsonOptions {
+    /// Write one object per address instead of one object per record.
+    pub per_address: bool,
+    /// Add date of last update into each object instead of writing it as the first line.
+    pub updated_field: bool,
+}
+
+#[derive(Serialize)]
+struct NdjsonHeader<'a> {
+    updated: &'a zicsv::DateTime,
+}
+
+#[derive(Serialize)]
+struct NdjsonRecord<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated: Option<&'a zicsv::DateTime>,
+    #[serde(flatten)]
+    record: &'a zicsv::Record,
+}
+
+#[derive(Serialize)]
+struct NdjsonAddress<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    updated: Option<&'a zicsv::DateTime>,
+    kind: &'static str,
+    address: String,
+    organization: &'a str,
+    document_id: &'a str,
+    document_date: &'a zicsv::Date,
+}
+
+fn write_line<StreamWriter, Value>(writer: &mut StreamWriter, value: &Value) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+    Value: serde::Serialize,
+{
+    serde_json::to_writer(&mut *writer, value)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+/// Write one JSON object per line, without reading the whole list into memory.
+pub fn into_ndjson<StreamWriter>(
+    options: &NdjsonOptions,
+    mut reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let updated = *reader.get_timestamp();
+    let updated_field = if options.updated_field {
+        Some(&updated)
+    } else {
+        write_line(writer, &NdjsonHeader { updated: &updated })?;
+        None
+    };
+
+    let mut n_errors = 0usize;
+    for record in reader.iter() {
+        match record {
+            Ok(record) => {
+                if options.per_address {
+                    for address in &record.addresses {
+                        write_line(
+                            writer,
+                            &NdjsonAddress {
+                                updated: updated_field,
+                                kind: export::address_kind(address),
+                                address: String::from(address),
+                                organization: &record.organization,
+                                document_id: &record.document_id,
+                                document_date: &record.document_date,
+                            },
+                        )?;
+                    }
+                } else {
+                    write_line(
+                        writer,
+                        &NdjsonRecord {
+                            updated: updated_field,
+                            record: &record,
+                        },
+                    )?;
+                }
+            },
+
+            Err(error) => {
+                n_errors += 1;
+                print_err::print_error(&error);
+            },
+        }
+    }
+
+    ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std;
+
+    #[cfg(feature = "msgpack")]
+    use rmp_serde;
+    #[cfg(feature = "cbor")]
+    use serde_cbor;
+    #[cfg(any(feature = "cbor", feature = "msgpack"))]
+    use serde_json;
+
+    use zicsv;
+
+    const LIST: &str = "\
+                        Updated: 2017-11-29 13:34:56 +0000\n\
+                        1.2.3.4;example.com;;Org 1;1-2-3;2017-01-02\n\
+                        ;*.example.org;;Org 2;4-5-6;2017-01-03\n\
+                        ";
+
+    #[cfg(any(feature = "cbor", feature = "msgpack", feature = "toml", feature = "yaml"))]
+    fn convert(output_format: &super::super::OutputFormat) -> Vec<u8> {
+        let reader = zicsv::Reader::from_reader(std::io::Cursor::new(LIST.to_string())).unwrap();
+        let mut output = Vec::new();
+        super::convert(output_format, Box::new(reader), &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn convert_normalized() {
+        let list = "\
+                    Updated: 2017-11-29 13:34:56 +0000\n\
+                    1.2.3.4;example.com;;Org 1;1-2-3;2017-01-02\n\
+                    ;*.example.org;;Org 2;4-5-6;2017-01-03\n\
+                    4.3.2.1;;;Org 1;7-8-9;2017-01-04\n\
+                    ;example.net;;Org 1;1-2-3;2017-01-02\n\
+                    ";
+        let reader = zicsv::Reader::from_reader(std::io::Cursor::new(list.to_string())).unwrap();
+        let mut output = Vec::new();
+        super::convert_normalized(&super::super::OutputFormat::JSON, Box::new(reader), &mut output).unwrap();
+
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"updated\":\"2017-11-29T13:34:56\",\
+             \"organizations\":[{\"id\":1,\"name\":\"Org 1\"},{\"id\":2,\"name\":\"Org 2\"}],\
+             \"documents\":[\
+             {\"id\":1,\"organization\":1,\"document_id\":\"1-2-3\",\"document_date\":\"2017-01-02\"},\
+             {\"id\":2,\"organization\":2,\"document_id\":\"4-5-6\",\"document_date\":\"2017-01-03\"},\
+             {\"id\":3,\"organization\":1,\"document_id\":\"7-8-9\",\"document_date\":\"2017-01-04\"}],\
+             \"addresses\":[\
+             {\"document\":1,\"address\":{\"IPv4\":\"1.2.3.4\"}},\
+             {\"document\":1,\"address\":{\"DomainName\":\"example.com\"}},\
+             {\"document\":2,\"address\":{\"WildcardDomainName\":\"*.example.org\"}},\
+             {\"document\":3,\"address\":{\"IPv4\":\"4.3.2.1\"}},\
+             {\"document\":1,\"address\":{\"DomainName\":\"example.net\"}}]}"
+        );
+    }
+
+    /// Test list as decoded from binary formats, where IPv4 addresses are arrays of bytes.
+    #[cfg(any(feature = "cbor", feature = "msgpack"))]
+    fn binary_value() -> serde_json::Value {
+        serde_json::from_str(
+            "{\"updated\":\"2017-11-29T13:34:56\",\"records\":[\
+             {\"addresses\":[{\"IPv4\":[1,2,3,4]},{\"DomainName\":\"example.com\"}],\"organization\":\"Org 1\",\
+             \"document_id\":\"1-2-3\",\"document_date\":\"2017-01-02\"},\
+             {\"addresses\":[{\"WildcardDomainName\":\"*.example.org\"}],\"organization\":\"Org 2\",\
+             \"document_id\":\"4-5-6\",\"document_date\":\"2017-01-03\"}]}",
+        ).unwrap()
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn convert_yaml() {
+        assert_eq!(
+            String::from_utf8(convert(&super::super::OutputFormat::YAML)).unwrap(),
+            "\
+             ---\n\
+             updated: \"2017-11-29T13:34:56\"\n\
+             records:\n  \
+             - addresses:\n      \
+             - IPv4: 1.2.3.4\n      \
+             - DomainName: example.com\n    \
+             organization: Org 1\n    \
+             document_id: 1-2-3\n    \
+             document_date: 2017-01-02\n  \
+             - addresses:\n      \
+             - WildcardDomainName: \"*.example.org\"\n    \
+             organization: Org 2\n    \
+             document_id: 4-5-6\n    \
+             document_date: 2017-01-03\n\
+             "
+        );
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn convert_toml() {
+        assert_eq!(
+            String::from_utf8(convert(&super::super::OutputFormat::TOML)).unwrap(),
+            "\
+             updated = \"2017-11-29T13:34:56\"\n\
+             \n\
+             [[records]]\n\
+             organization = \"Org 1\"\n\
+             document_id = \"1-2-3\"\n\
+             document_date = \"2017-01-02\"\n\
+             \n\
+             [[records.addresses]]\n\
+             IPv4 = \"1.2.3.4\"\n\
+             \n\
+             [[records.addresses]]\n\
+             DomainName = \"example.com\"\n\
+             \n\
+             [[records]]\n\
+             organization = \"Org 2\"\n\
+             document_id = \"4-5-6\"\n\
+             document_date = \"2017-01-03\"\n\
+             \n\
+             [[records.addresses]]\n\
+             WildcardDomainName = \"*.example.org\"\n\
+             "
+        );
+    }
+
+    #[cfg(feature = "cbor")]
+    #[test]
+    fn convert_cbor() {
+        assert_eq!(
+            serde_cbor::from_slice::<serde_json::Value>(&convert(&super::super::OutputFormat::CBOR)).unwrap(),
+            binary_value()
+        );
+    }
+
+    #[cfg(feature = "msgpack")]
+    #[test]
+    fn convert_msgpack() {
+        assert_eq!(
+            rmp_serde::from_slice::<serde_json::Value>(&convert(&super::super::OutputFormat::MessagePack)).unwrap(),
+            binary_value()
+        );
+    }
+
+    fn into_ndjson(per_address: bool, updated_field: bool) -> String {
+        let reader = zicsv::Reader::from_reader(std::io::Cursor::new(LIST.to_string())).unwrap();
+        let mut output = Vec::new();
+        super::into_ndjson(
+            &super::NdjsonOptions {
+                per_address,
+                updated_field,
+            },
+            Box::new(reader),
+            &mut output,
+        ).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn ndjson_records() {
+        assert_eq!(
+            into_ndjson(false, false),
+            "\
+             {\"updated\":\"2017-11-29T13:34:56\"}\n\
+             {\"addresses\":[{\"IPv4\":\"1.2.3.4\"},{\"DomainName\":\"example.com\"}],\"organization\":\"Org 1\",\
+             \"document_id\":\"1-2-3\",\"document_date\":\"2017-01-02\"}\n\
+             {\"addresses\":[{\"WildcardDomainName\":\"*.example.org\"}],\"organization\":\"Org 2\",\
+             \"document_id\":\"4-5-6\",\"document_date\":\"2017-01-03\"}\n\
+             "
+        );
+    }
+
+    #[test]
+    fn ndjson_addresses() {
+        assert_eq!(
+            into_ndjson(true, true),
+            "\
+             {\"updated\":\"2017-11-29T13:34:56\",\"kind\":\"IPv4\",\"address\":\"1.2.3.4\",\
+             \"organization\":\"Org 1\",\"document_id\":\"1-2-3\",\"document_date\":\"2017-01-02\"}\n\
+             {\"updated\":\"2017-11-29T13:34:56\",\"kind\":\"DomainName\",\"address\":\"example.com\",\
+             \"organization\":\"Org 1\",\"document_id\":\"1-2-3\",\"document_date\":\"2017-01-02\"}\n\
+             {\"updated\":\"2017-11-29T13:34:56\",\"kind\":\"WildcardDomainName\",\"address\":\"*.example.org\",\
+             \"organization\":\"Org 2\",\"document_id\":\"4-5-6\",\"document_date\":\"2017-01-03\"}\n\
+             "
+        );
+    }
+
+    #[test]
+    fn ndjson_invalid_list() {
+        let reader = zicsv::Reader::from_reader(std::io::Cursor::new(
+            "Updated: 2017-11-29 13:34:56 +0000\ninvalid;;;Org 1;1-2-3;2017-01-02\n".to_string(),
+        )).unwrap();
+        let mut output = Vec::new();
+        assert!(
+            super::into_ndjson(
+                &super::NdjsonOptions {
+                    per_address: false,
+                    updated_field: false,
+                },
+                Box::new(reader),
+                &mut output,
+            ).is_err()
+        );
+    }
+}
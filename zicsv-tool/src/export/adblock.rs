@@ -0,0 +1,80 @@
+use std;
+
+use failure;
+
+/// Rule which matches exactly one URL.
+///
+/// Adblock syntax has no escaping, so characters with special meaning are replaced by "*" wildcard.
+fn url_rule(url: &str) -> String {
+    let url: String = url.chars()
+        .map(|c| match c {
+            '*' | '^' | '|' | '$' => '*',
+            _ => c,
+        })
+        .collect();
+    format!("|{}|", url)
+}
+
+/// Write filter list in Adblock Plus syntax.
+///
+/// Blocked URLs are matched exactly, without blocking their hosts. Rule for domain name also blocks all its
+/// subdomains, rule for wildcard domain name also blocks its base domain name (with a warning).
+pub fn export<StreamWriter>(list: &super::List, writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let domains = list.domains_without_urls();
+    let exact_names: std::collections::BTreeSet<&str> = domains
+        .iter()
+        .filter(|domain| !domain.wildcard)
+        .map(|domain| domain.name.as_str())
+        .collect();
+    let n_wildcards = domains
+        .iter()
+        .filter(|domain| domain.wildcard && !exact_names.contains(domain.name.as_str()))
+        .count();
+
+    let mut names: Vec<&str> = domains.iter().map(|domain| domain.name.as_str()).collect();
+    // Domains are sorted by name, so exact and wildcard domain names with the same base are adjacent.
+    names.dedup();
+
+    writeln!(writer, "[Adblock Plus 2.0]")?;
+    writeln!(writer, "! Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    for name in names {
+        writeln!(writer, "||{}^", name)?;
+    }
+    for url in list.urls() {
+        writeln!(writer, "{}", url_rule(&url))?;
+    }
+
+    if n_wildcards > 0 {
+        eprintln!(
+            "Warning: {} wildcard domain names also block their base domain names in adblock format",
+            n_wildcards
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn url_rule() {
+        assert_eq!(
+            super::url_rule("http://example.com/a*b^c|d$e?f=g"),
+            "|http://example.com/a*b*c*d*e?f=g|"
+        );
+    }
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            tests::export(|list, output| super::export(list, output)),
+            include_str!("testdata/adblock.txt")
+        );
+    }
+}
@@ -0,0 +1,84 @@
+use std;
+
+use failure;
+
+/// Write static protocol for BIRD 2.
+///
+/// Routes point to next hop or are blackholed if next hop is not specified. Communities are added by import filter
+/// of protocol.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+    writeln!(writer, "protocol static {} {{", options.route_name)?;
+
+    if options.communities.is_empty() {
+        writeln!(writer, "\tipv4;")?;
+    } else {
+        writeln!(writer, "\tipv4 {{")?;
+        writeln!(writer, "\t\timport filter {{")?;
+        for community in &options.communities {
+            match *community {
+                super::Community::Standard(asn, value) => {
+                    writeln!(writer, "\t\t\tbgp_community.add(({}, {}));", asn, value)?
+                },
+                super::Community::Large(asn, value1, value2) => writeln!(
+                    writer,
+                    "\t\t\tbgp_large_community.add(({}, {}, {}));",
+                    asn, value1, value2
+                )?,
+            }
+        }
+        writeln!(writer, "\t\t\taccept;")?;
+        writeln!(writer, "\t\t}};")?;
+        writeln!(writer, "\t}};")?;
+    }
+
+    for route in list.ipv4_routes(options.route_aggregate) {
+        if let Some(next_hop) = options.next_hop {
+            writeln!(writer, "\troute {} via {};", super::format_ipv4_network(&route), next_hop)?;
+        } else {
+            writeln!(writer, "\troute {} blackhole;", super::format_ipv4_network(&route))?;
+        }
+    }
+
+    writeln!(writer, "}}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export_blackhole() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/bird-blackhole.conf")
+        );
+    }
+
+    #[test]
+    fn export_next_hop() {
+        let options = super::super::ExportOptions {
+            next_hop: Some("10.0.0.1".parse().unwrap()),
+            communities: vec!["65000:100".parse().unwrap(), "65000:1:2".parse().unwrap()],
+            route_aggregate: true,
+            route_name: "blocked".into(),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/bird.conf")
+        );
+    }
+}
@@ -0,0 +1,40 @@
+use std;
+
+use failure;
+
+/// Write rule provider with "classical" behavior for Clash.
+///
+/// Wildcard domain name is represented by "DOMAIN-SUFFIX" rule, which also matches its base domain name.
+pub fn export<StreamWriter>(list: &super::List, writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+    writeln!(writer, "payload:")?;
+
+    for domain in list.domains() {
+        if domain.wildcard {
+            writeln!(writer, "  - DOMAIN-SUFFIX,{}", domain.name)?;
+        } else {
+            writeln!(writer, "  - DOMAIN,{}", domain.name)?;
+        }
+    }
+    for network in list.ipv4_routes(true) {
+        writeln!(writer, "  - IP-CIDR,{}", super::format_ipv4_network(&network))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            tests::export(|list, output| super::export(list, output)),
+            include_str!("testdata/clash.yaml")
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use std;
+
+use failure;
+
+use zicsv;
+
+use print_err;
+
+/// Quote field if it contains delimiter, quotes or line breaks, as described in RFC 4180.
+fn quote_field(value: &str, delimiter: char) -> std::borrow::Cow<str> {
+    if value.contains(|c| c == delimiter || c == '"' || c == '\r' || c == '\n') {
+        std::borrow::Cow::Owned(format!("\"{}\"", value.replace('"', "\"\"")))
+    } else {
+        std::borrow::Cow::Borrowed(value)
+    }
+}
+
+fn write_row<StreamWriter>(fields: &[&str], delimiter: char, writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let row: Vec<_> = fields.iter().map(|field| quote_field(field, delimiter)).collect();
+    writeln!(writer, "{}", row.join(&delimiter.to_string()))?;
+    Ok(())
+}
+
+/// Write UTF-8 CSV with header and one row per address.
+///
+/// Source line is the number of line where record starts in original list, the same as in error messages of reader.
+/// Records are written while reading, invalid records are reported and skipped, export fails after the whole list.
+pub fn export<StreamWriter>(
+    delimiter: char,
+    mut reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    ensure!(
+        delimiter != '"' && delimiter != '\r' && delimiter != '\n',
+        "Invalid CSV delimiter: {:?}",
+        delimiter
+    );
+
+    write_row(
+        &[
+            "kind",
+            "address",
+            "organization",
+            "document_id",
+            "document_date",
+            "source_line",
+        ],
+        delimiter,
+        writer,
+    )?;
+
+    let mut n_errors = 0usize;
+    for (line_n, record) in reader.iter_numbered() {
+        let record = match record {
+            Ok(record) => record,
+
+            Err(error) => {
+                n_errors += 1;
+                print_err::print_error(&error);
+                continue;
+            },
+        };
+
+        let document_date = record.document_date.format("%Y-%m-%d").to_string();
+        let line_n = line_n.to_string();
+
+        for address in &record.addresses {
+            write_row(
+                &[
+                    super::address_kind(address),
+                    &String::from(address),
+                    &record.organization,
+                    &record.document_id,
+                    &document_date,
+                    &line_n,
+                ],
+                delimiter,
+                writer,
+            )?;
+        }
+    }
+
+    ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    fn export(delimiter: char, lines: &str) -> String {
+        let mut output = Vec::new();
+        super::export(delimiter, tests::reader("2017-11-29 13:34:56 +0000", lines), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn export_csv() {
+        let mut output = Vec::new();
+        super::export(',', tests::list_reader(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), include_str!("testdata/addresses.csv"));
+    }
+
+    #[test]
+    fn export_tsv() {
+        let mut output = Vec::new();
+        super::export('\t', tests::list_reader(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), include_str!("testdata/addresses.tsv"));
+    }
+
+    #[test]
+    fn quote_field() {
+        assert_eq!(super::quote_field("Org 1", ','), "Org 1");
+        assert_eq!(super::quote_field("Org, 1", ','), "\"Org, 1\"");
+        assert_eq!(super::quote_field("Org, 1", ';'), "Org, 1");
+        assert_eq!(super::quote_field("\"Org\" 1", ','), "\"\"\"Org\"\" 1\"");
+        assert_eq!(super::quote_field("Org\n1", '\t'), "\"Org\n1\"");
+    }
+
+    #[test]
+    fn export_quoted() {
+        assert_eq!(
+            export(',', ";example.com;;\"Org \"\"1\"\", Inc.\";1;2017-01-02\n"),
+            "\
+             kind,address,organization,document_id,document_date,source_line\n\
+             DomainName,example.com,\"Org \"\"1\"\", Inc.\",1,2017-01-02,2\n\
+             "
+        );
+    }
+
+    #[test]
+    fn export_multiline_record() {
+        assert_eq!(
+            export(',', ";example.com;;\"Org\n1\";1;2017-01-02\n;example.net;;Org 2;2;2017-01-02\n"),
+            "\
+             kind,address,organization,document_id,document_date,source_line\n\
+             DomainName,example.com,\"Org\n1\",1,2017-01-02,2\n\
+             DomainName,example.net,Org 2,2,2017-01-02,4\n\
+             "
+        );
+    }
+
+    #[test]
+    fn export_invalid_record() {
+        let mut output = Vec::new();
+        let reader = tests::reader("2017-11-29 13:34:56 +0000", ";example.com;;Org;1;invalid\n");
+        assert!(super::export(',', reader, &mut output).is_err());
+    }
+
+    #[test]
+    fn invalid_delimiter() {
+        let mut output = Vec::new();
+        assert!(super::export('"', tests::list_reader(), &mut output).is_err());
+    }
+}
@@ -0,0 +1,73 @@
+use std;
+
+use failure;
+
+/// Write configuration for dnsmasq.
+///
+/// dnsmasq matches the domain name together with all its subdomains, so exact and wildcard domain names produce the
+/// same line. With nftables set specified, resolved addresses are added into this set instead of blocking resolution.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    for name in list.domain_subtrees() {
+        if let Some(ref nftset) = options.dnsmasq_nftset {
+            writeln!(writer, "nftset=/{}/4#{}", name, nftset)?;
+        } else if let Some(redirect) = options.redirect {
+            writeln!(writer, "address=/{}/{}", name, redirect)?;
+        } else {
+            writeln!(writer, "address=/{}/", name)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export_nxdomain() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/dnsmasq-nxdomain.conf")
+        );
+    }
+
+    #[test]
+    fn export_redirect() {
+        let options = super::super::ExportOptions {
+            redirect: Some("10.0.0.1".parse().unwrap()),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/dnsmasq-redirect.conf")
+        );
+    }
+
+    #[test]
+    fn export_nftset() {
+        let options = super::super::ExportOptions {
+            dnsmasq_nftset: Some("inet#zicsv#zicsv_resolved".into()),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/dnsmasq-nftset.conf")
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use std;
+
+use failure;
+
+/// Write static routes for FRRouting.
+///
+/// Routes point to next hop or are blackholed if next hop is not specified. Prefix list and route map with the same
+/// name match exported routes and set communities, route map should be used for redistribution of static routes into
+/// BGP ("redistribute static route-map NAME"). Configuration contains only currently blocked routes, so it should be
+/// applied with full replace (e.g. "frr-reload.py --reload" as a part of the whole configuration) to remove routes
+/// which are not blocked anymore.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "! Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    let routes: Vec<_> = list.ipv4_routes(options.route_aggregate)
+        .iter()
+        .map(super::format_ipv4_network)
+        .collect();
+    for route in &routes {
+        if let Some(next_hop) = options.next_hop {
+            writeln!(writer, "ip route {} {}", route, next_hop)?;
+        } else {
+            writeln!(writer, "ip route {} blackhole", route)?;
+        }
+    }
+
+    // Explicit sequence numbers are the same as in output of "show running-config".
+    for (seq, route) in (1..).map(|n| n * 5).zip(&routes) {
+        writeln!(writer, "ip prefix-list {} seq {} permit {}", options.route_name, seq, route)?;
+    }
+
+    let mut standard = Vec::new();
+    let mut large = Vec::new();
+    for community in &options.communities {
+        match *community {
+            super::Community::Standard(..) => standard.push(community.to_string()),
+            super::Community::Large(..) => large.push(community.to_string()),
+        }
+    }
+
+    writeln!(writer, "route-map {} permit 10", options.route_name)?;
+    writeln!(writer, " match ip address prefix-list {}", options.route_name)?;
+    if !standard.is_empty() {
+        writeln!(writer, " set community {} additive", standard.join(" "))?;
+    }
+    if !large.is_empty() {
+        writeln!(writer, " set large-community {} additive", large.join(" "))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export_blackhole() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/frr-blackhole.conf")
+        );
+    }
+
+    #[test]
+    fn export_next_hop() {
+        let options = super::super::ExportOptions {
+            next_hop: Some("10.0.0.1".parse().unwrap()),
+            communities: vec![
+                "65000:100".parse().unwrap(),
+                "65000:1:2".parse().unwrap(),
+                "65000:200".parse().unwrap(),
+            ],
+            route_aggregate: true,
+            route_name: "blocked".into(),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/frr.conf")
+        );
+    }
+}
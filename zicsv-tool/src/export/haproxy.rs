@@ -0,0 +1,38 @@
+use std;
+
+use failure;
+
+/// Write map file for HAProxy.
+///
+/// Keys are regular expressions for `map_reg` converter: exact match of domain name or match of its subdomains.
+/// Values are legal basis of blocking.
+pub fn export<StreamWriter>(list: &super::List, writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    for domain in list.domains() {
+        let key = if domain.wildcard {
+            format!("\\.{}$", super::escape_regex(&domain.name))
+        } else {
+            format!("^{}$", super::escape_regex(&domain.name))
+        };
+        writeln!(writer, "{} {}", key, super::legal_basis(&domain.records))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            tests::export(|list, output| super::export(list, output)),
+            include_str!("testdata/haproxy.map")
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use std;
+
+use failure;
+
+/// Write hosts file.
+///
+/// Hosts file maps only exact names, so wildcard domain names are skipped with a warning. Blocked names are mapped
+/// into "0.0.0.0" or into address of block page.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let address = options
+        .redirect
+        .unwrap_or_else(|| std::net::Ipv4Addr::new(0, 0, 0, 0));
+    let mut n_wildcards = 0usize;
+
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    for domain in list.domains() {
+        if domain.wildcard {
+            n_wildcards += 1;
+        } else {
+            writeln!(writer, "{} {}", address, domain.name)?;
+        }
+    }
+
+    if n_wildcards > 0 {
+        eprintln!(
+            "Warning: {} wildcard domain names are skipped, hosts file is unable to block subdomains",
+            n_wildcards
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export_nxdomain() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/hosts-nxdomain.txt")
+        );
+    }
+
+    #[test]
+    fn export_redirect() {
+        let options = super::super::ExportOptions {
+            redirect: Some("10.0.0.1".parse().unwrap()),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/hosts-redirect.txt")
+        );
+    }
+}
@@ -0,0 +1,180 @@
+use std;
+
+use failure;
+
+/// Default maximum number of elements of ipset.
+const MIN_MAXELEM: usize = 65_536;
+
+/// Leave enough space for the list to grow until next update.
+fn maxelem(n_elements: usize) -> usize {
+    std::cmp::max(MIN_MAXELEM, (n_elements * 2).next_power_of_two())
+}
+
+fn write_set<StreamWriter, Elements>(
+    writer: &mut StreamWriter,
+    name: &str,
+    set_type: &str,
+    elements: Elements,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+    Elements: ExactSizeIterator<Item = String>,
+{
+    // New set is filled under temporary name and atomically swapped with the old one.
+    let temp_name = format!("{}-new", name);
+    let maxelem = maxelem(elements.len());
+
+    // Live set is created only if it does not exist yet. Its maxelem is not set here: swap gives it the temporary set
+    // together with maxelem of the temporary set.
+    writeln!(writer, "create {} {} family inet -exist", name, set_type)?;
+    // Temporary set may be left by previously interrupted restore.
+    writeln!(writer, "create {} {} family inet maxelem {} -exist", temp_name, set_type, maxelem)?;
+    writeln!(writer, "flush {}", temp_name)?;
+    for element in elements {
+        writeln!(writer, "add {} {}", temp_name, element)?;
+    }
+    writeln!(writer, "swap {} {}", temp_name, name)?;
+    writeln!(writer, "destroy {}", temp_name)?;
+
+    Ok(())
+}
+
+fn write_set_delta<StreamWriter, Element, FormatFn>(
+    writer: &mut StreamWriter,
+    name: &str,
+    removed: &std::collections::BTreeSet<Element>,
+    added: &std::collections::BTreeSet<Element>,
+    unchanged: &std::collections::BTreeSet<Element>,
+    format: FormatFn,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+    Element: Ord,
+    FormatFn: Fn(&Element) -> String,
+{
+    // Elements are added before deletion, so address moving into another network stays blocked. Element of changed
+    // record may also be present in other records.
+    for element in added.iter().filter(|element| !removed.contains(element) && !unchanged.contains(element)) {
+        writeln!(writer, "add {} {} -exist", name, format(element))?;
+    }
+    for element in removed.iter().filter(|element| !added.contains(element) && !unchanged.contains(element)) {
+        writeln!(writer, "del {} {} -exist", name, format(element))?;
+    }
+
+    Ok(())
+}
+
+/// Write script for `ipset restore`.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let ipv4_addresses: Vec<_> = list.ipv4_addresses().iter().map(|ipv4| ipv4.to_string()).collect();
+    write_set(writer, &options.ipset_ip_set, "hash:ip", ipv4_addresses.into_iter())?;
+
+    let ipv4_networks: Vec<_> = list.ipv4_networks().iter().map(super::format_ipv4_network).collect();
+    write_set(writer, &options.ipset_net_set, "hash:net", ipv4_networks.into_iter())
+}
+
+/// Write script for `ipset restore` which updates sets created from the previous list.
+pub fn export_delta<StreamWriter>(
+    options: &super::ExportOptions,
+    diff: &super::ListDiff,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    write_set_delta(
+        writer,
+        &options.ipset_ip_set,
+        &diff.removed.ipv4_addresses(),
+        &diff.added.ipv4_addresses(),
+        &diff.unchanged.ipv4_addresses(),
+        |ipv4| ipv4.to_string(),
+    )?;
+    write_set_delta(
+        writer,
+        &options.ipset_net_set,
+        &diff.removed.ipv4_networks(),
+        &diff.added.ipv4_networks(),
+        &diff.unchanged.ipv4_networks(),
+        super::format_ipv4_network,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std;
+
+    use super::super::tests;
+
+    #[test]
+    fn maxelem() {
+        assert_eq!(super::maxelem(0), 65_536);
+        assert_eq!(super::maxelem(32_768), 65_536);
+        assert_eq!(super::maxelem(32_769), 131_072);
+    }
+
+    #[test]
+    fn write_set_with_grown_maxelem() {
+        let write_set = |n_elements: u32| {
+            let elements: Vec<_> = (0..n_elements).map(|i| std::net::Ipv4Addr::from(i).to_string()).collect();
+            let mut output = Vec::new();
+            super::write_set(&mut output, "zicsv-ip", "hash:ip", elements.into_iter()).unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let create_lines = |script: &str| -> Vec<String> {
+            script
+                .lines()
+                .filter(|line| line.starts_with("create "))
+                .map(String::from)
+                .collect()
+        };
+
+        // Regenerated script should not set maxelem of existing live set created by the previous script.
+        assert_eq!(
+            create_lines(&write_set(1)),
+            vec![
+                "create zicsv-ip hash:ip family inet -exist",
+                "create zicsv-ip-new hash:ip family inet maxelem 65536 -exist",
+            ]
+        );
+        assert_eq!(
+            create_lines(&write_set(32_769)),
+            vec![
+                "create zicsv-ip hash:ip family inet -exist",
+                "create zicsv-ip-new hash:ip family inet maxelem 131072 -exist",
+            ]
+        );
+    }
+
+    #[test]
+    fn export() {
+        let options = super::super::ExportOptions {
+            ipset_ip_set: "blocked-ip".into(),
+            ipset_net_set: "blocked-net".into(),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/ipset.txt")
+        );
+    }
+
+    #[test]
+    fn export_delta() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export_delta(|diff, output| super::export_delta(&options, diff, output)),
+            include_str!("testdata/ipset-delta.txt")
+        );
+    }
+}
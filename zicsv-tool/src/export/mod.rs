@@ -0,0 +1,779 @@
+use std;
+
+use failure;
+use ipnet;
+use url;
+
+use zicsv;
+
+use print_err;
+
+mod adblock;
+mod bird;
+mod clash;
+mod csv;
+mod dnsmasq;
+mod frr;
+mod haproxy;
+mod hosts;
+mod ipset;
+mod nftables;
+mod nginx;
+mod pac;
+#[cfg(feature = "parquet-export")]
+mod parquet;
+mod routeros;
+mod rpz;
+mod singbox;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+mod squid;
+mod suricata;
+#[cfg(feature = "template")]
+mod template;
+mod unbound;
+mod v2ray;
+
+#[derive(Debug)]
+pub enum ExportFormat {
+    IPSet,
+    NFTables,
+    RPZ,
+    Unbound,
+    Dnsmasq,
+    PAC,
+    Squid,
+    Hosts,
+    Adblock,
+    RouterOS,
+    BIRD,
+    FRR,
+    Suricata,
+    Clash,
+    SingBox,
+    V2Ray,
+    NginxMap,
+    HAProxyMap,
+    #[cfg(feature = "sqlite")]
+    SQLite,
+    #[cfg(feature = "parquet-export")]
+    Parquet,
+    CSV,
+    TSV,
+    /// Custom format defined by template in given file, not selectable by name.
+    #[cfg(feature = "template")]
+    Template(String),
+}
+
+impl ExportFormat {
+    pub fn variants() -> Vec<&'static str> {
+        let mut variants = vec![
+            "ipset", "nftables", "rpz", "unbound", "dnsmasq", "pac", "squid", "hosts", "adblock", "routeros", "bird",
+            "frr", "suricata", "clash", "sing-box", "v2ray", "nginx-map", "haproxy-map", "csv", "tsv",
+        ];
+        if cfg!(feature = "sqlite") {
+            variants.push("sqlite");
+        }
+        if cfg!(feature = "parquet-export") {
+            variants.push("parquet");
+        }
+        variants
+    }
+
+    /// Format used when template is specified instead of format.
+    #[cfg(feature = "template")]
+    pub fn template(template_path: String) -> Result<Self, failure::Error> {
+        Ok(ExportFormat::Template(template_path))
+    }
+
+    /// Format used when template is specified instead of format.
+    #[cfg(not(feature = "template"))]
+    pub fn template(_template_path: String) -> Result<Self, failure::Error> {
+        bail!("Export using templates is not supported, zicsv-tool is built without \"template\" feature")
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ipset" => Ok(ExportFormat::IPSet),
+            "nftables" => Ok(ExportFormat::NFTables),
+            "rpz" => Ok(ExportFormat::RPZ),
+            "unbound" => Ok(ExportFormat::Unbound),
+            "dnsmasq" => Ok(ExportFormat::Dnsmasq),
+            "pac" => Ok(ExportFormat::PAC),
+            "squid" => Ok(ExportFormat::Squid),
+            "hosts" => Ok(ExportFormat::Hosts),
+            "adblock" => Ok(ExportFormat::Adblock),
+            "routeros" => Ok(ExportFormat::RouterOS),
+            "bird" => Ok(ExportFormat::BIRD),
+            "frr" => Ok(ExportFormat::FRR),
+            "suricata" => Ok(ExportFormat::Suricata),
+            "clash" => Ok(ExportFormat::Clash),
+            "sing-box" => Ok(ExportFormat::SingBox),
+            "v2ray" => Ok(ExportFormat::V2Ray),
+            "nginx-map" => Ok(ExportFormat::NginxMap),
+            "haproxy-map" => Ok(ExportFormat::HAProxyMap),
+            #[cfg(feature = "sqlite")]
+            "sqlite" => Ok(ExportFormat::SQLite),
+            #[cfg(feature = "parquet-export")]
+            "parquet" => Ok(ExportFormat::Parquet),
+            "csv" => Ok(ExportFormat::CSV),
+            "tsv" => Ok(ExportFormat::TSV),
+
+            unknown_format => Err(format_err!("Unknown export format: \"{}\"", unknown_format)),
+        }
+    }
+}
+
+impl std::fmt::Display for ExportFormat {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(
+            formatter,
+            "{}",
+            match *self {
+                ExportFormat::IPSet => "ipset",
+                ExportFormat::NFTables => "nftables",
+                ExportFormat::RPZ => "rpz",
+                ExportFormat::Unbound => "unbound",
+                ExportFormat::Dnsmasq => "dnsmasq",
+                ExportFormat::PAC => "pac",
+                ExportFormat::Squid => "squid",
+                ExportFormat::Hosts => "hosts",
+                ExportFormat::Adblock => "adblock",
+                ExportFormat::RouterOS => "routeros",
+                ExportFormat::BIRD => "bird",
+                ExportFormat::FRR => "frr",
+                ExportFormat::Suricata => "suricata",
+                ExportFormat::Clash => "clash",
+                ExportFormat::SingBox => "sing-box",
+                ExportFormat::V2Ray => "v2ray",
+                ExportFormat::NginxMap => "nginx-map",
+                ExportFormat::HAProxyMap => "haproxy-map",
+                #[cfg(feature = "sqlite")]
+                ExportFormat::SQLite => "sqlite",
+                #[cfg(feature = "parquet-export")]
+                ExportFormat::Parquet => "parquet",
+                ExportFormat::CSV => "csv",
+                ExportFormat::TSV => "tsv",
+                #[cfg(feature = "template")]
+                ExportFormat::Template(_) => "template",
+            }
+        )
+    }
+}
+
+/// BGP community attached to exported routes.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Community {
+    /// Regular community: "ASN:value".
+    Standard(u16, u16),
+    /// Large community: "ASN:value:value".
+    Large(u32, u32, u32),
+}
+
+impl std::str::FromStr for Community {
+    type Err = failure::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<_> = s.split(':').collect();
+        match parts.len() {
+            2 => Ok(Community::Standard(
+                parts[0].parse().map_err(|_| format_err!("Invalid BGP community: \"{}\"", s))?,
+                parts[1].parse().map_err(|_| format_err!("Invalid BGP community: \"{}\"", s))?,
+            )),
+
+            3 => Ok(Community::Large(
+                parts[0].parse().map_err(|_| format_err!("Invalid BGP large community: \"{}\"", s))?,
+                parts[1].parse().map_err(|_| format_err!("Invalid BGP large community: \"{}\"", s))?,
+                parts[2].parse().map_err(|_| format_err!("Invalid BGP large community: \"{}\"", s))?,
+            )),
+
+            _ => Err(format_err!("Invalid BGP community: \"{}\"", s)),
+        }
+    }
+}
+
+impl std::fmt::Display for Community {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        match *self {
+            Community::Standard(asn, value) => write!(formatter, "{}:{}", asn, value),
+            Community::Large(asn, value1, value2) => write!(formatter, "{}:{}:{}", asn, value1, value2),
+        }
+    }
+}
+
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub previous_path: Option<String>,
+    pub redirect: Option<std::net::Ipv4Addr>,
+
+    pub ipset_ip_set: String,
+    pub ipset_net_set: String,
+
+    pub nft_family: String,
+    pub nft_table: String,
+    pub nft_ip_set: String,
+    pub nft_net_set: String,
+
+    pub rpz_ns: String,
+
+    pub dnsmasq_nftset: Option<String>,
+
+    pub pac_proxy: String,
+    pub pac_resolve: bool,
+
+    pub squid_acl_prefix: String,
+
+    pub routeros_list: String,
+    pub routeros_tag: String,
+    pub routeros_timeout: Option<String>,
+    pub routeros_domains: bool,
+
+    pub next_hop: Option<std::net::Ipv4Addr>,
+    pub communities: Vec<Community>,
+    pub route_aggregate: bool,
+    pub route_name: String,
+
+    pub suricata_sid_base: u32,
+
+    /// Overrides default delimiter of "csv" and "tsv" formats.
+    pub csv_delimiter: Option<char>,
+}
+
+/// Blocked domain name.
+pub struct Domain<'a> {
+    /// Domain name without "*." prefix.
+    pub name: String,
+    /// Only subdomains are blocked, not the domain name itself.
+    pub wildcard: bool,
+    /// Records which block this domain name.
+    pub records: Vec<&'a zicsv::Record>,
+}
+
+fn is_valid_domain_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= 253 && name.split('.').all(|label| {
+        !label.is_empty() && label.len() <= 63
+            && label
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    })
+}
+
+/// Whole list read into memory.
+pub struct List {
+    pub updated: zicsv::DateTime,
+    pub records: Vec<zicsv::Record>,
+}
+
+impl List {
+    fn from_reader(mut reader: Box<zicsv::GenericReader>) -> Result<Self, failure::Error> {
+        let mut n_errors = 0usize;
+        let mut records = Vec::new();
+
+        for record in reader.iter() {
+            match record {
+                Ok(record) => records.push(record),
+
+                Err(error) => {
+                    n_errors += 1;
+                    print_err::print_error(&error);
+                },
+            }
+        }
+
+        // Exporting incomplete list would silently unblock addresses.
+        ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
+
+        Ok(Self {
+            updated: *reader.get_timestamp(),
+            records,
+        })
+    }
+
+    fn addresses<'a>(&'a self) -> Box<Iterator<Item = &'a zicsv::Address> + 'a> {
+        Box::new(self.records.iter().flat_map(|record| record.addresses.iter()))
+    }
+
+    /// Sorted unique blocked domain names, wildcard domain names and hosts of blocked URLs.
+    ///
+    /// Wildcard "*" which matches all domain names and names not usable in DNS are skipped with a warning.
+    pub fn domains(&self) -> Vec<Domain> {
+        self.collect_domains(true)
+    }
+
+    /// Same as `domains()`, but without hosts of blocked URLs.
+    ///
+    /// For formats which are able to block URLs separately.
+    pub fn domains_without_urls(&self) -> Vec<Domain> {
+        self.collect_domains(false)
+    }
+
+    fn collect_domains(&self, url_hosts: bool) -> Vec<Domain> {
+        let mut domains = std::collections::BTreeMap::new();
+        let mut n_match_all = 0usize;
+        let mut n_invalid = 0usize;
+
+        for record in &self.records {
+            for address in &record.addresses {
+                let (name, wildcard) = match *address {
+                    zicsv::Address::DomainName(ref name) => (name.as_str(), false),
+
+                    zicsv::Address::WildcardDomainName(ref name) => {
+                        if name == "*" {
+                            n_match_all += 1;
+                            continue;
+                        }
+                        (name.trim_left_matches("*."), true)
+                    },
+
+                    zicsv::Address::URL(ref url) if url_hosts => match url.host() {
+                        Some(url::Host::Domain(name)) => (name, false),
+
+                        // Not a domain name.
+                        _ => continue,
+                    },
+
+                    _ => continue,
+                };
+
+                let name = name.trim_right_matches('.');
+                if !is_valid_domain_name(name) {
+                    n_invalid += 1;
+                    continue;
+                }
+
+                let records = domains
+                    .entry((name.to_string(), wildcard))
+                    .or_insert_with(Vec::new);
+                if !records.contains(&record) {
+                    records.push(record);
+                }
+            }
+        }
+
+        if n_match_all > 0 {
+            eprintln!(
+                "Warning: {} wildcards \"*\" matching all domain names are skipped",
+                n_match_all
+            );
+        }
+        if n_invalid > 0 {
+            eprintln!("Warning: {} invalid domain names are skipped", n_invalid);
+        }
+
+        domains
+            .into_iter()
+            .map(|((name, wildcard), records)| Domain {
+                name,
+                wildcard,
+                records,
+            })
+            .collect()
+    }
+
+    /// Sorted unique domain names to be blocked together with all their subdomains.
+    ///
+    /// For formats which can not distinguish domain name from its subdomains. Wildcard domain name is represented by
+    /// its base domain name.
+    pub fn domain_subtrees(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.domains().into_iter().map(|domain| domain.name).collect();
+        // Domains are sorted by name, so exact and wildcard domain names with the same base are adjacent.
+        names.dedup();
+        names
+    }
+
+    /// Sorted unique blocked URLs without fragments.
+    pub fn urls(&self) -> std::collections::BTreeSet<String> {
+        self.addresses()
+            .filter_map(|address| match *address {
+                zicsv::Address::URL(ref url) => {
+                    // Fragment is never sent to server.
+                    let mut url = url.clone();
+                    url.set_fragment(None);
+                    Some(url.into_string())
+                },
+
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sorted unique blocked IPv4 addresses.
+    pub fn ipv4_addresses(&self) -> std::collections::BTreeSet<std::net::Ipv4Addr> {
+        self.addresses()
+            .filter_map(|address| match *address {
+                zicsv::Address::IPv4(ipv4) => Some(ipv4),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Sorted unique blocked IPv4 networks for routing, addresses are represented by networks with prefix length 32.
+    ///
+    /// With aggregation networks are merged into minimal set of networks which covers the same IPv4 space.
+    pub fn ipv4_routes(&self, aggregate: bool) -> Vec<ipnet::Ipv4Net> {
+        if aggregate {
+            zicsv::aggregate_ipv4(self.addresses().filter(|address| match **address {
+                zicsv::Address::IPv4(_) | zicsv::Address::IPv4Network(_) => true,
+                _ => false,
+            }))
+        } else {
+            let mut routes = self.ipv4_networks();
+            routes.extend(
+                self.ipv4_addresses()
+                    .into_iter()
+                    .map(ipv4_host_network),
+            );
+            routes.into_iter().collect()
+        }
+    }
+
+    /// Sorted unique blocked IPv4 networks with host bits cleared.
+    pub fn ipv4_networks(&self) -> std::collections::BTreeSet<ipnet::Ipv4Net> {
+        self.addresses()
+            .filter_map(|address| match *address {
+                zicsv::Address::IPv4Network(network) => Some(network.trunc()),
+                _ => None,
+            })
+            .collect()
+    }
+}
+
+/// IPv4 address as network with prefix length 32.
+pub fn ipv4_host_network(ipv4: std::net::Ipv4Addr) -> ipnet::Ipv4Net {
+    ipnet::Ipv4Net::new(ipv4, 32).expect("Prefix length 32 is valid")
+}
+
+/// IPv4 network in "address/prefix length" format.
+pub fn format_ipv4_network(network: &ipnet::Ipv4Net) -> String {
+    format!("{}/{}", network.addr(), network.prefix_len())
+}
+
+/// Changes between previous and current lists, as found by `zicsv::diff()`.
+///
+/// Addresses of changed records may also be present in other records, so exporters of changes should check them
+/// against the rest of records.
+pub struct ListDiff {
+    /// Records present only in the previous list, with timestamp of the previous list.
+    pub removed: List,
+    /// Records present only in the current list.
+    pub added: List,
+    /// Records present in both lists.
+    pub unchanged: List,
+}
+
+impl ListDiff {
+    fn from_readers(
+        previous: &mut zicsv::GenericReader,
+        current: &mut zicsv::GenericReader,
+    ) -> Result<Self, failure::Error> {
+        let zicsv::Diff {
+            removed,
+            added,
+            unchanged,
+        } = zicsv::diff(previous, current)?;
+
+        let updated = *current.get_timestamp();
+        Ok(Self {
+            removed: List {
+                updated: *previous.get_timestamp(),
+                records: removed,
+            },
+            added: List {
+                updated,
+                records: added,
+            },
+            unchanged: List {
+                updated,
+                records: unchanged,
+            },
+        })
+    }
+}
+
+/// Name of address type, the same as in JSON output.
+pub fn address_kind(address: &zicsv::Address) -> &'static str {
+    match *address {
+        zicsv::Address::IPv4(_) => "IPv4",
+        zicsv::Address::IPv4Network(_) => "IPv4Network",
+        zicsv::Address::DomainName(_) => "DomainName",
+        zicsv::Address::WildcardDomainName(_) => "WildcardDomainName",
+        zicsv::Address::URL(_) => "URL",
+        _ => "Unknown",
+    }
+}
+
+/// Legal basis of blocking: "organization|document id|document date" of each record, delimited by ";".
+pub fn legal_basis(records: &[&zicsv::Record]) -> String {
+    records
+        .iter()
+        .map(|record| {
+            format!(
+                "{}|{}|{}",
+                record.organization,
+                record.document_id,
+                record.document_date.format("%Y-%m-%d")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(";")
+}
+
+/// Escape characters which have special meaning in POSIX extended regular expressions.
+pub fn escape_regex(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' | '^' | '$' | '.' | '|' | '?' | '*' | '+' | '(' | ')' | '[' | ']' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            },
+
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Export only changes between previous and current lists.
+fn export_delta<StreamWriter>(
+    options: &ExportOptions,
+    previous_path: &str,
+    mut reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let mut previous_reader = zicsv::Reader::from_file(previous_path)?;
+    let diff = ListDiff::from_readers(&mut previous_reader, &mut *reader)?;
+
+    match options.format {
+        ExportFormat::IPSet => ipset::export_delta(options, &diff, writer),
+        ExportFormat::NFTables => nftables::export_delta(options, &diff, writer),
+
+        ref format => bail!("Export format \"{}\" does not support export of changes", format),
+    }
+}
+
+pub fn export<StreamWriter>(
+    options: &ExportOptions,
+    reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    if let Some(ref previous_path) = options.previous_path {
+        return export_delta(options, previous_path, reader, writer);
+    }
+
+    match options.format {
+        ExportFormat::IPSet => ipset::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::NFTables => nftables::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::RPZ => rpz::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::Unbound => unbound::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::Dnsmasq => dnsmasq::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::PAC => pac::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::Squid => squid::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::Hosts => hosts::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::Adblock => adblock::export(&List::from_reader(reader)?, writer),
+        ExportFormat::RouterOS => routeros::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::BIRD => bird::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::FRR => frr::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::Suricata => suricata::export(options, &List::from_reader(reader)?, writer),
+        ExportFormat::Clash => clash::export(&List::from_reader(reader)?, writer),
+        ExportFormat::SingBox => singbox::export(&List::from_reader(reader)?, writer),
+        ExportFormat::V2Ray => v2ray::export(&List::from_reader(reader)?, writer),
+        ExportFormat::NginxMap => nginx::export(&List::from_reader(reader)?, writer),
+        ExportFormat::HAProxyMap => haproxy::export(&List::from_reader(reader)?, writer),
+        #[cfg(feature = "sqlite")]
+        ExportFormat::SQLite => sqlite::export(&List::from_reader(reader)?, writer),
+        #[cfg(feature = "parquet-export")]
+        ExportFormat::Parquet => parquet::export(reader, writer),
+        ExportFormat::CSV => csv::export(options.csv_delimiter.unwrap_or(','), reader, writer),
+        ExportFormat::TSV => csv::export(options.csv_delimiter.unwrap_or('\t'), reader, writer),
+        #[cfg(feature = "template")]
+        ExportFormat::Template(ref template_path) => {
+            template::export(template_path, &List::from_reader(reader)?, writer)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std;
+
+    use failure;
+
+    use zicsv;
+    use zicsv::chrono;
+
+    use super::{ExportFormat, ExportOptions, List};
+
+    /// Options with default values of command line arguments.
+    pub fn options() -> ExportOptions {
+        ExportOptions {
+            format: ExportFormat::IPSet,
+            previous_path: None,
+            redirect: None,
+
+            ipset_ip_set: "zicsv-ip".into(),
+            ipset_net_set: "zicsv-net".into(),
+
+            nft_family: "inet".into(),
+            nft_table: "zicsv".into(),
+            nft_ip_set: "zicsv_ip".into(),
+            nft_net_set: "zicsv_net".into(),
+
+            rpz_ns: "localhost.".into(),
+
+            dnsmasq_nftset: None,
+
+            pac_proxy: "PROXY localhost:3128".into(),
+            pac_resolve: false,
+
+            squid_acl_prefix: "zicsv".into(),
+
+            routeros_list: "zicsv".into(),
+            routeros_tag: "zicsv".into(),
+            routeros_timeout: None,
+            routeros_domains: false,
+
+            next_hop: None,
+            communities: Vec::new(),
+            route_aggregate: false,
+            route_name: "zicsv".into(),
+
+            suricata_sid_base: 1_000_000_000,
+
+            csv_delimiter: None,
+        }
+    }
+
+    /// Lines of list used for all golden file tests.
+    const LIST: &str = "\
+                        1.2.3.4;example.com;http://example.com/;Org 1;1-2-3;2017-01-02\n\
+                        1.2.3.0/24 | 4.3.2.1;*.example.org;;Org 2;4-5-6;2017-01-02\n\
+                        1.2.3.4 | 10.20.30.41/30;;http://example.net/path?a=b;Org 1;7-8-9;2017-01-02\n\
+                        ;* | xn--e1aybc.org;http://xn--e1aybc.org:8080/test;Org 3;10;2017-01-02\n\
+                        ";
+
+    /// Lines of previous version of the test list for tests of export of changes. The last record is the same as in
+    /// the current list.
+    const PREVIOUS_LIST: &str = "\
+                                 1.2.3.4 | 5.6.7.8;;;Org 1;1-2-3;2017-01-02\n\
+                                 1.2.2.0/23;;;Org 2;4-5-6;2017-01-02\n\
+                                 1.2.3.4 | 10.20.30.41/30;;http://example.net/path?a=b;Org 1;7-8-9;2017-01-02\n\
+                                 ";
+
+    /// Reader of list with given date of last update and lines.
+    pub fn reader(updated: &str, lines: &str) -> Box<zicsv::GenericReader> {
+        let list = format!("Updated: {}\n{}", updated, lines);
+        Box::new(zicsv::Reader::from_reader(std::io::Cursor::new(list)).unwrap())
+    }
+
+    /// Records parsed from lines of list without header.
+    pub fn records(lines: &str) -> Vec<zicsv::Record> {
+        reader("2017-11-29 13:34:56 +0000", lines)
+            .iter()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    /// Reader of list used for all golden file tests.
+    pub fn list_reader() -> Box<zicsv::GenericReader> {
+        reader("2017-11-29 13:34:56 +0000", LIST)
+    }
+
+    /// List used for all golden file tests.
+    pub fn list() -> List {
+        List {
+            updated: chrono::NaiveDate::from_ymd(2017, 11, 29).and_hms(13, 34, 56),
+            records: records(LIST),
+        }
+    }
+
+    #[test]
+    fn domains() {
+        let list = List {
+            records: records(
+                ";*.example.com | example.com | * | test.*.example.org | *.xn--e1aybc.org;\
+                 http://example.com/test | http://1.2.3.4/test;Org 1;1-2-3;2017-01-02\n",
+            ),
+
+            ..list()
+        };
+
+        let domains: Vec<_> = list.domains()
+            .into_iter()
+            .map(|domain| (domain.name, domain.wildcard, domain.records.len()))
+            .collect();
+        assert_eq!(
+            domains,
+            vec![
+                ("example.com".to_string(), false, 1),
+                ("example.com".to_string(), true, 1),
+                ("xn--e1aybc.org".to_string(), true, 1),
+            ]
+        );
+
+        assert_eq!(list.domain_subtrees(), vec!["example.com", "xn--e1aybc.org"]);
+    }
+
+    #[test]
+    fn parse_community() {
+        use super::Community;
+
+        assert_eq!("65000:100".parse::<Community>().unwrap(), Community::Standard(65000, 100));
+        assert_eq!(
+            "4200000000:1:2".parse::<Community>().unwrap(),
+            Community::Large(4_200_000_000, 1, 2)
+        );
+        assert_eq!(format!("{}", Community::Large(4_200_000_000, 1, 2)), "4200000000:1:2");
+
+        assert!("65000".parse::<Community>().is_err());
+        assert!("65536:100".parse::<Community>().is_err());
+        assert!("65000:x".parse::<Community>().is_err());
+        assert!("1:2:3:4".parse::<Community>().is_err());
+    }
+
+    #[test]
+    fn escape_regex() {
+        assert_eq!(
+            super::escape_regex("http://example.com/a.b?c=(d)&e=[f]|g*+{2}^$\\"),
+            "http://example\\.com/a\\.b\\?c=\\(d\\)&e=\\[f\\]\\|g\\*\\+\\{2\\}\\^\\$\\\\"
+        );
+    }
+
+    /// Changes between the previous and current test lists.
+    pub fn diff() -> super::ListDiff {
+        super::ListDiff::from_readers(
+            &mut *reader("2017-11-28 13:34:56 +0000", PREVIOUS_LIST),
+            &mut *reader("2017-11-29 13:34:56 +0000", LIST),
+        ).unwrap()
+    }
+
+    /// Run exporter of changes on changes between the previous and current test lists and return output as string.
+    pub fn export_delta<ExportFn>(export_fn: ExportFn) -> String
+    where
+        ExportFn: FnOnce(&super::ListDiff, &mut Vec<u8>) -> Result<(), failure::Error>,
+    {
+        let mut output = Vec::new();
+        export_fn(&diff(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Run exporter on the test list and return output as string.
+    pub fn export<ExportFn>(export_fn: ExportFn) -> String
+    where
+        ExportFn: FnOnce(&List, &mut Vec<u8>) -> Result<(), failure::Error>,
+    {
+        let mut output = Vec::new();
+        export_fn(&list(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+}
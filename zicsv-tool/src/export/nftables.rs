@@ -0,0 +1,207 @@
+use std;
+
+use failure;
+use ipnet;
+
+use zicsv;
+
+/// Number of elements in one "add element" command. Whole file is applied in one transaction anyway.
+const ELEMENTS_PER_COMMAND: usize = 1000;
+
+fn format_element(network: &ipnet::Ipv4Net) -> String {
+    if network.prefix_len() == 32 {
+        network.addr().to_string()
+    } else {
+        super::format_ipv4_network(network)
+    }
+}
+
+fn write_element_commands<StreamWriter>(
+    options: &super::ExportOptions,
+    command: &str,
+    set_name: &str,
+    elements: &[ipnet::Ipv4Net],
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    for chunk in elements.chunks(ELEMENTS_PER_COMMAND) {
+        let chunk: Vec<_> = chunk.iter().map(format_element).collect();
+        writeln!(
+            writer,
+            "{} element {} {} {} {{ {} }}",
+            command,
+            options.nft_family,
+            options.nft_table,
+            set_name,
+            chunk.join(", ")
+        )?;
+    }
+
+    Ok(())
+}
+
+fn write_elements<StreamWriter>(
+    options: &super::ExportOptions,
+    set_name: &str,
+    elements: &[ipnet::Ipv4Net],
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(
+        writer,
+        "flush set {} {} {}",
+        options.nft_family, options.nft_table, set_name
+    )?;
+    write_element_commands(options, "add", set_name, elements, writer)
+}
+
+/// Intervals of address set. Intervals in set should not overlap.
+fn ipv4_address_elements(list: &super::List) -> Vec<ipnet::Ipv4Net> {
+    zicsv::aggregate_ipv4(list.addresses().filter(|address| match **address {
+        zicsv::Address::IPv4(_) => true,
+        _ => false,
+    }))
+}
+
+/// Intervals of network set.
+fn ipv4_network_elements(list: &super::List) -> Vec<ipnet::Ipv4Net> {
+    zicsv::aggregate_ipv4(list.addresses().filter(|address| match **address {
+        zicsv::Address::IPv4Network(_) => true,
+        _ => false,
+    }))
+}
+
+/// Write file for `nft -f`.
+///
+/// Sets are declared (this does nothing if they already exist), flushed and filled again. `nft` applies the whole file
+/// in one transaction, so there is no moment when sets are empty.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "#!/usr/sbin/nft -f")?;
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+    writeln!(writer)?;
+
+    writeln!(writer, "table {} {} {{", options.nft_family, options.nft_table)?;
+    for set_name in &[&options.nft_ip_set, &options.nft_net_set] {
+        writeln!(writer, "\tset {} {{", set_name)?;
+        writeln!(writer, "\t\ttype ipv4_addr")?;
+        writeln!(writer, "\t\tflags interval")?;
+        writeln!(writer, "\t\tauto-merge")?;
+        writeln!(writer, "\t}}")?;
+    }
+    writeln!(writer, "}}")?;
+    writeln!(writer)?;
+
+    write_elements(options, &options.nft_ip_set, &ipv4_address_elements(list), writer)?;
+    write_elements(options, &options.nft_net_set, &ipv4_network_elements(list), writer)
+}
+
+fn write_elements_delta<StreamWriter>(
+    options: &super::ExportOptions,
+    set_name: &str,
+    removed: &[ipnet::Ipv4Net],
+    added: &[ipnet::Ipv4Net],
+    unchanged: &[ipnet::Ipv4Net],
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    // Compare covered address space instead of elements: address may move into a wider network or a network may be
+    // split into smaller ones without any change of the set. Space of changed records may also be covered by other
+    // records.
+    let added_space = zicsv::exclude_ipv4_networks(&zicsv::exclude_ipv4_networks(added, unchanged), removed);
+    let removed_space = zicsv::exclude_ipv4_networks(&zicsv::exclude_ipv4_networks(removed, unchanged), added);
+
+    write_element_commands(options, "add", set_name, &added_space, writer)?;
+    write_element_commands(options, "delete", set_name, &removed_space, writer)
+}
+
+/// Write file for `nft -f` which updates sets created from the previous list.
+///
+/// Sets should use `auto-merge` flag: removed range may be only a part of an element.
+pub fn export_delta<StreamWriter>(
+    options: &super::ExportOptions,
+    diff: &super::ListDiff,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "#!/usr/sbin/nft -f")?;
+    writeln!(
+        writer,
+        "# Generated by zicsv-tool from changes between lists updated at {} UTC and {} UTC.",
+        diff.removed.updated, diff.added.updated
+    )?;
+    writeln!(writer)?;
+
+    write_elements_delta(
+        options,
+        &options.nft_ip_set,
+        &ipv4_address_elements(&diff.removed),
+        &ipv4_address_elements(&diff.added),
+        &ipv4_address_elements(&diff.unchanged),
+        writer,
+    )?;
+    write_elements_delta(
+        options,
+        &options.nft_net_set,
+        &ipv4_network_elements(&diff.removed),
+        &ipv4_network_elements(&diff.added),
+        &ipv4_network_elements(&diff.unchanged),
+        writer,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export() {
+        let options = super::super::ExportOptions {
+            nft_family: "ip".into(),
+            nft_table: "filter".into(),
+            nft_ip_set: "blocked_ip".into(),
+            nft_net_set: "blocked_net".into(),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/nftables.nft")
+        );
+    }
+
+    #[test]
+    fn export_defaults() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/nftables-defaults.nft")
+        );
+    }
+
+    #[test]
+    fn export_delta() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export_delta(|diff, output| super::export_delta(&options, diff, output)),
+            include_str!("testdata/nftables-delta.nft")
+        );
+    }
+}
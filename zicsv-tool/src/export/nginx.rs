@@ -0,0 +1,103 @@
+use std;
+
+use failure;
+
+/// Quote string for nginx configuration.
+///
+/// Values of map may contain variables and nginx has no escaping for "$", so it is written as "${dollar}".
+fn quote_string(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('$', "${dollar}")
+    )
+}
+
+/// Write entries of nginx map with "hostnames" parameter.
+///
+/// Each blocked host name is mapped into legal basis of blocking. Wildcard domain name is written as "*.example.com".
+/// If both domain name and its subdomains are blocked, special form ".example.com" is used. Configuration should define
+/// variable "$dollar" which contains dollar sign: `geo $dollar { default "$"; }`.
+pub fn export<StreamWriter>(list: &super::List, writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    let mut domains = list.domains().into_iter().peekable();
+    while let Some(domain) = domains.next() {
+        // Exact domain name goes right before wildcard domain name with the same base.
+        let has_wildcard = !domain.wildcard && domains
+            .peek()
+            .map_or(false, |next| next.wildcard && next.name == domain.name);
+
+        let (host, records) = if has_wildcard {
+            let mut records = domain.records;
+            for record in domains.next().expect("Wildcard domain name").records {
+                if !records.contains(&record) {
+                    records.push(record);
+                }
+            }
+            (format!(".{}", domain.name), records)
+        } else if domain.wildcard {
+            (format!("*.{}", domain.name), domain.records)
+        } else {
+            (domain.name, domain.records)
+        };
+
+        writeln!(
+            writer,
+            "{} {};",
+            host,
+            quote_string(&super::legal_basis(&records))
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn quote_string() {
+        assert_eq!(super::quote_string("Org 1"), "\"Org 1\"");
+        assert_eq!(
+            super::quote_string("\"Org\" \\1 $2 ${3}"),
+            "\"\\\"Org\\\" \\\\1 ${dollar}2 ${dollar}{3}\""
+        );
+    }
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            tests::export(|list, output| super::export(list, output)),
+            include_str!("testdata/nginx.map")
+        );
+    }
+
+    #[test]
+    fn export_domain_with_subdomains() {
+        let list = super::super::List {
+            records: tests::records(
+                ";example.com;;\"Org \"\"1\"\"\";1;2017-01-02\n\
+                 ;*.example.com | example.com;;Org 2;2;2017-01-02\n",
+            ),
+
+            ..tests::list()
+        };
+
+        let mut output = Vec::new();
+        super::export(&list, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\
+             # Generated by zicsv-tool from list updated at 2017-11-29 13:34:56 UTC.\n\
+             .example.com \"Org \\\"1\\\"|1|2017-01-02;Org 2|2|2017-01-02\";\n\
+             "
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use std;
+
+use failure;
+use serde_json;
+
+use zicsv;
+
+/// Flag of domain lookup table: domain name itself is blocked.
+const DOMAIN_EXACT: u8 = 1;
+/// Flag of domain lookup table: subdomains are blocked.
+const DOMAIN_SUBDOMAINS: u8 = 2;
+
+/// Lookup table of blocked domain names with flags.
+fn domain_flags(list: &super::List) -> std::collections::BTreeMap<String, u8> {
+    let mut flags = std::collections::BTreeMap::new();
+    for domain in list.domains() {
+        let flag = if domain.wildcard {
+            DOMAIN_SUBDOMAINS
+        } else {
+            DOMAIN_EXACT
+        };
+        *flags.entry(domain.name).or_insert(0) |= flag;
+    }
+    flags
+}
+
+/// Sorted non-overlapping inclusive ranges of blocked IPv4 addresses, neighbouring ranges are merged.
+fn ipv4_ranges(list: &super::List) -> Vec<(u32, u32)> {
+    let networks = zicsv::aggregate_ipv4(list.addresses().filter(|address| match **address {
+        zicsv::Address::IPv4(_) | zicsv::Address::IPv4Network(_) => true,
+        _ => false,
+    }));
+
+    let mut ranges: Vec<(u32, u32)> = Vec::new();
+    for network in networks {
+        let first = u32::from(network.network());
+        let last = u32::from(network.broadcast());
+
+        if let Some(range) = ranges.last_mut() {
+            if u64::from(range.1) + 1 == u64::from(first) {
+                range.1 = last;
+                continue;
+            }
+        }
+        ranges.push((first, last));
+    }
+    ranges
+}
+
+const PAC_FUNCTIONS: &str = "\
+function ipv4ToNumber(address) {
+\tvar match = /^(\\d+)\\.(\\d+)\\.(\\d+)\\.(\\d+)$/.exec(address);
+\tif (!match)
+\t\treturn -1;
+\treturn ((+match[1] * 256 + +match[2]) * 256 + +match[3]) * 256 + +match[4];
+}
+
+function isBlockedIPv4(address) {
+\tvar number = ipv4ToNumber(address);
+\tif (number < 0)
+\t\treturn false;
+
+\t// Binary search of the first range which ends at or after the address.
+\tvar low = 0;
+\tvar high = ranges.length / 2;
+\twhile (low < high) {
+\t\tvar middle = (low + high) >> 1;
+\t\tif (ranges[middle * 2 + 1] < number)
+\t\t\tlow = middle + 1;
+\t\telse
+\t\t\thigh = middle;
+\t}
+\treturn low < ranges.length / 2 && ranges[low * 2] <= number;
+}
+
+function isBlockedDomain(host) {
+\t// Bitwise operations turn undefined and inherited properties of object into zero.
+\tif (domains[host] & 1)
+\t\treturn true;
+\tfor (var dot = host.indexOf(\".\"); dot >= 0; dot = host.indexOf(\".\", dot + 1))
+\t\tif (domains[host.substring(dot + 1)] & 2)
+\t\t\treturn true;
+\treturn false;
+}
+";
+
+/// Write proxy auto-config file.
+///
+/// Blocked domain names are looked up in object used as hash table, blocked IPv4 addresses are found by binary search
+/// in sorted array of ranges. Host is resolved by `dnsResolve()` only if requested, because this blocks the browser.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "// Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+    writeln!(writer)?;
+    writeln!(writer, "var proxy = {};", serde_json::to_string(&options.pac_proxy)?)?;
+    writeln!(writer)?;
+
+    writeln!(
+        writer,
+        "// Blocked domain names: {} - domain name itself, {} - its subdomains.",
+        DOMAIN_EXACT, DOMAIN_SUBDOMAINS
+    )?;
+    let domains: Vec<_> = domain_flags(list)
+        .into_iter()
+        .map(|(name, flags)| format!("\t\"{}\": {}", name, flags))
+        .collect();
+    writeln!(writer, "var domains = {{\n{}\n}};", domains.join(",\n"))?;
+    writeln!(writer)?;
+
+    writeln!(writer, "// Blocked IPv4 addresses: sorted pairs of the first and the last address of range.")?;
+    let ranges: Vec<_> = ipv4_ranges(list)
+        .into_iter()
+        .map(|(first, last)| format!("\t{}, {}", first, last))
+        .collect();
+    writeln!(writer, "var ranges = [\n{}\n];", ranges.join(",\n"))?;
+    writeln!(writer)?;
+
+    writeln!(writer, "{}", PAC_FUNCTIONS)?;
+
+    writeln!(writer, "function FindProxyForURL(url, host) {{")?;
+    writeln!(writer, "\thost = host.toLowerCase();")?;
+    writeln!(writer, "\tif (isBlockedDomain(host) || isBlockedIPv4(host))")?;
+    writeln!(writer, "\t\treturn proxy;")?;
+    if options.pac_resolve {
+        writeln!(writer, "\tvar address = dnsResolve(host);")?;
+        writeln!(writer, "\tif (address && isBlockedIPv4(address))")?;
+        writeln!(writer, "\t\treturn proxy;")?;
+    }
+    writeln!(writer, "\treturn \"DIRECT\";")?;
+    writeln!(writer, "}}")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "pac-tests")]
+    use quick_js;
+
+    use super::super::tests;
+
+    /// Evaluate generated file and call `FindProxyForURL()` for each host.
+    #[cfg(feature = "pac-tests")]
+    fn find_proxy(pac: &str, hosts: &[&str]) -> Vec<String> {
+        let context = quick_js::Context::new().unwrap();
+        // Browsers resolve names in their own way, test environment resolves only one name.
+        let _ = context
+            .eval("function dnsResolve(host) { return host === \"resolved.test\" ? \"4.3.2.1\" : null; }")
+            .unwrap();
+        let _ = context.eval(pac).unwrap();
+
+        hosts
+            .iter()
+            .map(|host| {
+                context
+                    .eval_as::<String>(&format!("FindProxyForURL(\"http://{0}/\", \"{0}\")", host))
+                    .unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn export() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/proxy.pac")
+        );
+    }
+
+    #[cfg(feature = "pac-tests")]
+    #[test]
+    fn find_proxy_for_url() {
+        let options = tests::options();
+        let pac = tests::export(|list, output| super::export(&options, list, output));
+
+        let blocked = [
+            "example.com",
+            "EXAMPLE.COM",
+            "test.example.org",
+            "a.b.example.org",
+            "example.net",
+            "xn--e1aybc.org",
+            "1.2.3.0",
+            "1.2.3.77",
+            "1.2.3.255",
+            "4.3.2.1",
+            "10.20.30.40",
+            "10.20.30.43",
+        ];
+        assert!(
+            find_proxy(&pac, &blocked)
+                .iter()
+                .all(|result| result == "PROXY localhost:3128")
+        );
+
+        let unblocked = [
+            "test.example.com",
+            "example.org",
+            "notexample.org",
+            "example.com.test",
+            "constructor",
+            "resolved.test",
+            "1.2.2.255",
+            "1.2.4.0",
+            "4.3.2.2",
+            "10.20.30.39",
+            "10.20.30.44",
+        ];
+        assert!(find_proxy(&pac, &unblocked).iter().all(|result| result == "DIRECT"));
+    }
+
+    #[cfg(feature = "pac-tests")]
+    #[test]
+    fn find_proxy_for_url_resolve() {
+        let options = super::super::ExportOptions {
+            pac_proxy: "PROXY proxy.example.com:8080; DIRECT".into(),
+            pac_resolve: true,
+
+            ..tests::options()
+        };
+        let pac = tests::export(|list, output| super::export(&options, list, output));
+
+        assert_eq!(
+            find_proxy(&pac, &["resolved.test", "unresolved.test"]),
+            vec!["PROXY proxy.example.com:8080; DIRECT", "DIRECT"]
+        );
+    }
+}
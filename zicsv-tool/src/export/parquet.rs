@@ -0,0 +1,271 @@
+use std;
+
+use arrow_array;
+use arrow_schema;
+use failure;
+use parquet;
+
+use zicsv;
+
+use print_err;
+
+/// Maximum number of rows in one record batch (and row group).
+const BATCH_SIZE: usize = 64 * 1024;
+
+fn schema() -> arrow_schema::SchemaRef {
+    let dictionary = || {
+        arrow_schema::DataType::Dictionary(
+            Box::new(arrow_schema::DataType::Int32),
+            Box::new(arrow_schema::DataType::Utf8),
+        )
+    };
+
+    std::sync::Arc::new(arrow_schema::Schema::new(vec![
+        arrow_schema::Field::new("kind", dictionary(), false),
+        arrow_schema::Field::new("value", arrow_schema::DataType::Utf8, false),
+        arrow_schema::Field::new("organization", dictionary(), false),
+        arrow_schema::Field::new("document_id", arrow_schema::DataType::Utf8, false),
+        arrow_schema::Field::new("document_date", arrow_schema::DataType::Date32, false),
+        arrow_schema::Field::new(
+            "snapshot",
+            arrow_schema::DataType::Timestamp(arrow_schema::TimeUnit::Second, Some("UTC".into())),
+            false,
+        ),
+    ]))
+}
+
+/// Builder of record batches with one row per address.
+struct TableBuilder {
+    schema: arrow_schema::SchemaRef,
+    snapshot: i64,
+    n_rows: usize,
+
+    kind: arrow_array::builder::StringDictionaryBuilder<arrow_array::types::Int32Type>,
+    value: arrow_array::builder::StringBuilder,
+    organization: arrow_array::builder::StringDictionaryBuilder<arrow_array::types::Int32Type>,
+    document_id: arrow_array::builder::StringBuilder,
+    document_date: arrow_array::builder::Date32Builder,
+    snapshot_column: arrow_array::builder::TimestampSecondBuilder,
+}
+
+impl TableBuilder {
+    fn new(updated: &zicsv::DateTime) -> Self {
+        Self {
+            schema: schema(),
+            snapshot: updated.timestamp(),
+            n_rows: 0,
+
+            kind: arrow_array::builder::StringDictionaryBuilder::new(),
+            value: arrow_array::builder::StringBuilder::new(),
+            organization: arrow_array::builder::StringDictionaryBuilder::new(),
+            document_id: arrow_array::builder::StringBuilder::new(),
+            document_date: arrow_array::builder::Date32Builder::new(),
+            snapshot_column: arrow_array::builder::TimestampSecondBuilder::new().with_timezone("UTC"),
+        }
+    }
+
+    fn append(&mut self, record: &zicsv::Record) -> Result<(), failure::Error> {
+        let epoch = zicsv::chrono::NaiveDate::from_ymd(1970, 1, 1);
+        let document_date = record.document_date.signed_duration_since(epoch).num_days() as i32;
+
+        for address in &record.addresses {
+            let _ = self.kind.append(super::address_kind(address))?;
+            self.value.append_value(String::from(address));
+            let _ = self.organization.append(&record.organization)?;
+            self.document_id.append_value(&record.document_id);
+            self.document_date.append_value(document_date);
+            self.snapshot_column.append_value(self.snapshot);
+        }
+        self.n_rows += record.addresses.len();
+
+        Ok(())
+    }
+
+    /// Take all appended rows. Dictionaries are built separately for each batch.
+    fn finish(&mut self) -> Result<arrow_array::RecordBatch, failure::Error> {
+        self.n_rows = 0;
+
+        Ok(arrow_array::RecordBatch::try_new(
+            self.schema.clone(),
+            vec![
+                std::sync::Arc::new(self.kind.finish()) as arrow_array::ArrayRef,
+                std::sync::Arc::new(self.value.finish()),
+                std::sync::Arc::new(self.organization.finish()),
+                std::sync::Arc::new(self.document_id.finish()),
+                std::sync::Arc::new(self.document_date.finish()),
+                std::sync::Arc::new(self.snapshot_column.finish()),
+            ],
+        )?)
+    }
+}
+
+/// Write Parquet file with one row per address.
+///
+/// Output is not streamed: the whole file is built in memory buffer, because Parquet writer requires thread-safe
+/// output, and is not written at all if list contains errors.
+pub fn export<StreamWriter>(
+    mut reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let mut n_errors = 0usize;
+    let mut table = TableBuilder::new(reader.get_timestamp());
+
+    let mut buffer = Vec::new();
+    {
+        let properties = parquet::file::properties::WriterProperties::builder()
+            .set_compression(parquet::basic::Compression::SNAPPY)
+            .build();
+        let mut parquet_writer =
+            parquet::arrow::ArrowWriter::try_new(&mut buffer, table.schema.clone(), Some(properties))?;
+
+        for record in reader.iter() {
+            match record {
+                Ok(record) => {
+                    table.append(&record)?;
+                    if table.n_rows >= BATCH_SIZE {
+                        parquet_writer.write(&table.finish()?)?;
+                    }
+                },
+
+                Err(error) => {
+                    n_errors += 1;
+                    print_err::print_error(&error);
+                },
+            }
+        }
+
+        if table.n_rows > 0 {
+            parquet_writer.write(&table.finish()?)?;
+        }
+        let _ = parquet_writer.close()?;
+    }
+
+    ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
+
+    writer.write_all(&buffer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std;
+
+    use arrow_array;
+    use failure;
+    use parquet;
+    use tempfile;
+
+    use zicsv;
+
+    const LIST: &str = "\
+                        Updated: 2017-11-29 13:34:56 +0000\n\
+                        1.2.3.4;example.com;http://example.com/;Org 1;1-2-3;2017-01-02\n\
+                        1.2.3.0/24 | 4.3.2.1;*.example.org;;Org 2;4-5-6;2017-01-02\n\
+                        1.2.3.4;;;Org 1;7-8-9;2017-01-03\n\
+                        ";
+
+    fn export(list: &str) -> Result<Vec<u8>, failure::Error> {
+        let reader = zicsv::Reader::from_reader(std::io::Cursor::new(list.to_string()))?;
+        let mut output = Vec::new();
+        super::export(Box::new(reader), &mut output)?;
+        Ok(output)
+    }
+
+    fn read_batches(data: &[u8]) -> Vec<arrow_array::RecordBatch> {
+        use std::io::Write;
+
+        let mut file = tempfile::tempfile().unwrap();
+        file.write_all(data).unwrap();
+
+        parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder::try_new(file)
+            .unwrap()
+            .build()
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+    }
+
+    fn strings(column: &arrow_array::ArrayRef) -> Vec<String> {
+        use arrow_array::Array;
+
+        if let Some(dictionary) =
+            column.as_any().downcast_ref::<arrow_array::DictionaryArray<arrow_array::types::Int32Type>>()
+        {
+            let values: &arrow_array::StringArray = dictionary.values().as_any().downcast_ref().unwrap();
+            dictionary
+                .keys()
+                .values()
+                .iter()
+                .map(|key| values.value(*key as usize).to_string())
+                .collect()
+        } else {
+            let values: &arrow_array::StringArray = column.as_any().downcast_ref().unwrap();
+            (0..values.len()).map(|i| values.value(i).to_string()).collect()
+        }
+    }
+
+    #[test]
+    fn export_table() {
+        use arrow_array::Array;
+
+        let batches = read_batches(&export(LIST).unwrap());
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+
+        assert_eq!(
+            strings(batch.column(0)),
+            vec!["IPv4", "DomainName", "URL", "IPv4Network", "IPv4", "WildcardDomainName", "IPv4"]
+        );
+        assert_eq!(
+            strings(batch.column(1)),
+            vec![
+                "1.2.3.4",
+                "example.com",
+                "http://example.com/",
+                "1.2.3.0/24",
+                "4.3.2.1",
+                "*.example.org",
+                "1.2.3.4",
+            ]
+        );
+        assert_eq!(
+            strings(batch.column(2)),
+            vec!["Org 1", "Org 1", "Org 1", "Org 2", "Org 2", "Org 2", "Org 1"]
+        );
+        assert_eq!(
+            strings(batch.column(3)),
+            vec!["1-2-3", "1-2-3", "1-2-3", "4-5-6", "4-5-6", "4-5-6", "7-8-9"]
+        );
+
+        // Organizations are dictionary encoded.
+        let organizations = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<arrow_array::DictionaryArray<arrow_array::types::Int32Type>>()
+            .unwrap();
+        assert_eq!(organizations.values().len(), 2);
+
+        let document_dates = batch
+            .column(4)
+            .as_any()
+            .downcast_ref::<arrow_array::Date32Array>()
+            .unwrap();
+        assert_eq!(document_dates.value(0), 17168);
+        assert_eq!(document_dates.value(6), 17169);
+
+        let snapshots = batch
+            .column(5)
+            .as_any()
+            .downcast_ref::<arrow_array::TimestampSecondArray>()
+            .unwrap();
+        assert!(snapshots.values().iter().all(|snapshot| *snapshot == 1_511_962_496));
+    }
+
+    #[test]
+    fn export_invalid_list() {
+        assert!(export("Updated: 2017-11-29 13:34:56 +0000\ninvalid;;;Org 1;1-2-3;2017-01-02\n").is_err());
+    }
+}
@@ -0,0 +1,186 @@
+use std;
+
+use failure;
+use ipnet;
+
+use zicsv;
+
+/// Quote string for RouterOS script.
+///
+/// Non-ASCII characters are written as hexadecimal escapes of their UTF-8 bytes.
+pub fn quote_string(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' | b'$' | b'?' => {
+                quoted.push('\\');
+                quoted.push(byte as char);
+            },
+
+            0x20...0x7e => quoted.push(byte as char),
+
+            _ => quoted.push_str(&format!("\\{:02X}", byte)),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn comment(tag: &str, records: &[&zicsv::Record]) -> String {
+    let documents: Vec<_> = records
+        .iter()
+        .map(|record| format!("{} {}", record.organization, record.document_id))
+        .collect();
+    format!("{}: {}", tag, documents.join(", "))
+}
+
+/// Blocked IPv4 addresses and networks with records which block them.
+fn ipv4_entries(list: &super::List) -> std::collections::BTreeMap<ipnet::Ipv4Net, Vec<&zicsv::Record>> {
+    let mut entries = std::collections::BTreeMap::new();
+    for record in &list.records {
+        for address in &record.addresses {
+            let network = match *address {
+                zicsv::Address::IPv4(ipv4) => super::ipv4_host_network(ipv4),
+                zicsv::Address::IPv4Network(network) => network.trunc(),
+                _ => continue,
+            };
+
+            let records = entries.entry(network).or_insert_with(Vec::new);
+            if !records.contains(&record) {
+                records.push(record);
+            }
+        }
+    }
+    entries
+}
+
+fn write_entry<StreamWriter>(
+    options: &super::ExportOptions,
+    address: &str,
+    records: &[&zicsv::Record],
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let list = quote_string(&options.routeros_list);
+    let address = quote_string(address);
+
+    // Address may be already added by somebody else, "add" fails on duplicate and aborts the whole script. Catching
+    // the error is cheaper than looking for the address before each "add".
+    write!(writer, ":do {{ add list={} address={}", list, address)?;
+    if let Some(ref timeout) = options.routeros_timeout {
+        write!(writer, " timeout={}", quote_string(timeout))?;
+    }
+    writeln!(
+        writer,
+        " comment={} }} on-error={{}}",
+        quote_string(&comment(&options.routeros_tag, records))
+    )?;
+    Ok(())
+}
+
+/// Write script for RouterOS which replaces entries of firewall address list.
+///
+/// Entries added by previous run of script are found by tag in the beginning of comment and removed, other entries of
+/// the same address list are not touched. Addresses already present in the list are not added again. Domain names are
+/// resolved by RouterOS itself, wildcard domain names are not supported and skipped with a warning.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+    writeln!(writer, "/ip firewall address-list")?;
+    writeln!(
+        writer,
+        "remove [find list={} comment~{}]",
+        quote_string(&options.routeros_list),
+        quote_string(&format!("^{}:", super::escape_regex(&options.routeros_tag)))
+    )?;
+
+    for (network, records) in ipv4_entries(list) {
+        let address = if network.prefix_len() == 32 {
+            network.addr().to_string()
+        } else {
+            super::format_ipv4_network(&network)
+        };
+        write_entry(options, &address, &records, writer)?;
+    }
+
+    if options.routeros_domains {
+        let mut n_wildcards = 0usize;
+        for domain in list.domains() {
+            if domain.wildcard {
+                n_wildcards += 1;
+            } else {
+                write_entry(options, &domain.name, &domain.records, writer)?;
+            }
+        }
+
+        if n_wildcards > 0 {
+            eprintln!(
+                "Warning: {} wildcard domain names are skipped, RouterOS address list is unable to block subdomains",
+                n_wildcards
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn quote_string() {
+        assert_eq!(
+            super::quote_string("a \"b\" \\c $d? \u{418}\u{434}"),
+            "\"a \\\"b\\\" \\\\c \\$d\\? \\D0\\98\\D0\\B4\""
+        );
+    }
+
+    #[test]
+    fn write_entry() {
+        let records = tests::records("1.2.3.4;;;Org;1;2017-01-02\n");
+
+        let mut output = Vec::new();
+        super::write_entry(&tests::options(), "1.2.3.4", &[&records[0]], &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            ":do { add list=\"zicsv\" address=\"1.2.3.4\" comment=\"zicsv: Org 1\" } on-error={}\n"
+        );
+    }
+
+    #[test]
+    fn export() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/routeros.rsc")
+        );
+    }
+
+    #[test]
+    fn export_domains() {
+        let options = super::super::ExportOptions {
+            routeros_list: "blocked".into(),
+            routeros_tag: "auto".into(),
+            routeros_timeout: Some("1d".into()),
+            routeros_domains: true,
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/routeros-domains.rsc")
+        );
+    }
+}
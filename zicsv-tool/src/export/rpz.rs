@@ -0,0 +1,147 @@
+use std;
+
+use failure;
+
+/// Escape string for use inside of quoted character string in zone file.
+pub fn escape_character_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b'\\' => {
+                escaped.push('\\');
+                escaped.push(byte as char);
+            },
+
+            0x20...0x7e => escaped.push(byte as char),
+
+            _ => escaped.push_str(&format!("\\{:03}", byte)),
+        }
+    }
+    escaped
+}
+
+/// Replace control characters, so value does not break out of zone file comment.
+fn comment_text(value: &str) -> String {
+    value.chars().map(|c| if c.is_control() { ' ' } else { c }).collect()
+}
+
+/// Write zone file for DNS Response Policy Zone.
+///
+/// Blocked domain names are rewritten into NXDOMAIN (`CNAME .`) or into the address of block page. Zone file comments
+/// contain organization and document ID of blocking records. For block page they are also available as TXT records
+/// (TXT records can not coexist with CNAME used for NXDOMAIN).
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "; Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+    writeln!(writer, "$TTL 3600")?;
+    // Serial is increased on every update of the list.
+    writeln!(
+        writer,
+        "@ IN SOA {} hostmaster.{} {} 3600 600 604800 3600",
+        options.rpz_ns,
+        options.rpz_ns,
+        list.updated.timestamp()
+    )?;
+    writeln!(writer, "@ IN NS {}", options.rpz_ns)?;
+
+    for domain in list.domains() {
+        writeln!(writer)?;
+
+        for record in &domain.records {
+            writeln!(
+                writer,
+                "; {}: {} ({})",
+                comment_text(&record.organization),
+                comment_text(&record.document_id),
+                record.document_date
+            )?;
+        }
+
+        let owner = if domain.wildcard {
+            format!("*.{}", domain.name)
+        } else {
+            domain.name
+        };
+
+        if let Some(redirect) = options.redirect {
+            writeln!(writer, "{} A {}", owner, redirect)?;
+            for record in &domain.records {
+                writeln!(
+                    writer,
+                    "{} TXT \"{}\" \"{}\" \"{}\"",
+                    owner,
+                    escape_character_string(&record.organization),
+                    escape_character_string(&record.document_id),
+                    record.document_date
+                )?;
+            }
+        } else {
+            writeln!(writer, "{} CNAME .", owner)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn escape_character_string() {
+        assert_eq!(super::escape_character_string(""), "");
+        assert_eq!(super::escape_character_string("Org 1"), "Org 1");
+        assert_eq!(super::escape_character_string("\"a\\b\""), "\\\"a\\\\b\\\"");
+        assert_eq!(
+            super::escape_character_string("\u{41e}\u{440}\u{433}"),
+            "\\208\\158\\209\\128\\208\\179"
+        );
+    }
+
+    #[test]
+    fn export_multiline_organization() {
+        let mut records = tests::records(";example.com;;Org;1-2-3;2017-01-02\n");
+        records[0].organization = "Org\n1\r\n2".into();
+        let list = super::super::List {
+            records,
+
+            ..tests::list()
+        };
+
+        let mut output = Vec::new();
+        super::export(&tests::options(), &list, &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.ends_with("\n; Org 1  2: 1-2-3 (2017-01-02)\nexample.com CNAME .\n"));
+    }
+
+    #[test]
+    fn export_nxdomain() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/rpz-nxdomain.zone")
+        );
+    }
+
+    #[test]
+    fn export_redirect() {
+        let options = super::super::ExportOptions {
+            rpz_ns: "ns.example.net.".into(),
+            redirect: Some("10.0.0.1".parse().unwrap()),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/rpz-redirect.zone")
+        );
+    }
+}
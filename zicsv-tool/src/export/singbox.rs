@@ -0,0 +1,69 @@
+use std;
+
+use failure;
+use serde_json;
+
+/// Version of rule set format.
+const RULE_SET_VERSION: u32 = 1;
+
+#[derive(Serialize)]
+struct HeadlessRule {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    domain: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    domain_suffix: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    ip_cidr: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct RuleSet {
+    version: u32,
+    rules: Vec<HeadlessRule>,
+}
+
+/// Write source rule set for sing-box.
+///
+/// Wildcard domain name is represented by domain suffix with leading dot, which does not match base domain name.
+pub fn export<StreamWriter>(list: &super::List, writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let mut rule = HeadlessRule {
+        domain: Vec::new(),
+        domain_suffix: Vec::new(),
+        ip_cidr: list.ipv4_routes(true)
+            .iter()
+            .map(super::format_ipv4_network)
+            .collect(),
+    };
+    for domain in list.domains() {
+        if domain.wildcard {
+            rule.domain_suffix.push(format!(".{}", domain.name));
+        } else {
+            rule.domain.push(domain.name);
+        }
+    }
+
+    let rule_set = RuleSet {
+        version: RULE_SET_VERSION,
+        rules: vec![rule],
+    };
+    serde_json::to_writer_pretty(&mut *writer, &rule_set)?;
+    writeln!(writer)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            tests::export(|list, output| super::export(list, output)),
+            include_str!("testdata/sing-box.json")
+        );
+    }
+}
@@ -0,0 +1,216 @@
+use std;
+
+use failure;
+use rusqlite;
+use tempfile;
+
+use zicsv;
+
+const SCHEMA: &str = "\
+CREATE TABLE list (
+    updated TEXT NOT NULL
+);
+
+CREATE TABLE records (
+    id INTEGER PRIMARY KEY,
+    organization TEXT NOT NULL,
+    document_id TEXT NOT NULL,
+    document_date TEXT NOT NULL
+);
+
+CREATE TABLE addresses (
+    record_id INTEGER NOT NULL REFERENCES records (id),
+    kind TEXT NOT NULL,
+    value TEXT NOT NULL,
+    ipv4_start INTEGER,
+    ipv4_end INTEGER,
+    reversed_value TEXT
+);
+";
+
+const INDEXES: &str = "\
+CREATE INDEX records_document_id ON records (document_id);
+CREATE INDEX addresses_record_id ON addresses (record_id);
+CREATE INDEX addresses_value ON addresses (value);
+CREATE INDEX addresses_ipv4_range ON addresses (ipv4_start, ipv4_end);
+CREATE INDEX addresses_reversed_value ON addresses (reversed_value);
+";
+
+/// Row of "addresses" table without record id.
+struct AddressRow {
+    kind: &'static str,
+    value: String,
+    ipv4_range: Option<(u32, u32)>,
+    reversed_value: Option<String>,
+}
+
+impl<'a> From<&'a zicsv::Address> for AddressRow {
+    fn from(address: &zicsv::Address) -> Self {
+        let (ipv4_range, reversed_value) = match *address {
+            zicsv::Address::IPv4(ipv4) => (Some((u32::from(ipv4), u32::from(ipv4))), None),
+            zicsv::Address::IPv4Network(network) => (
+                Some((u32::from(network.network()), u32::from(network.broadcast()))),
+                None,
+            ),
+            zicsv::Address::DomainName(ref name) | zicsv::Address::WildcardDomainName(ref name) => {
+                (None, Some(name.chars().rev().collect()))
+            },
+            _ => (None, None),
+        };
+
+        Self {
+            kind: super::address_kind(address),
+            value: String::from(address),
+            ipv4_range,
+            reversed_value,
+        }
+    }
+}
+
+fn write_database(connection: &mut rusqlite::Connection, list: &super::List) -> Result<(), failure::Error> {
+    let transaction = connection.transaction()?;
+    transaction.execute_batch(SCHEMA)?;
+
+    let _ = transaction.execute(
+        "INSERT INTO list (updated) VALUES (?1)",
+        &[&list.updated.format("%Y-%m-%d %H:%M:%S").to_string()],
+    )?;
+
+    {
+        let mut insert_record = transaction.prepare(
+            "INSERT INTO records (id, organization, document_id, document_date) VALUES (?1, ?2, ?3, ?4)",
+        )?;
+        let mut insert_address = transaction.prepare(
+            "INSERT INTO addresses (record_id, kind, value, ipv4_start, ipv4_end, reversed_value) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        )?;
+
+        for (id, record) in (1i64..).zip(&list.records) {
+            let _ = insert_record.execute(&[
+                &id as &rusqlite::ToSql,
+                &record.organization,
+                &record.document_id,
+                &record.document_date.format("%Y-%m-%d").to_string(),
+            ])?;
+
+            for address in &record.addresses {
+                let row = AddressRow::from(address);
+                let _ = insert_address.execute(&[
+                    &id as &rusqlite::ToSql,
+                    &row.kind,
+                    &row.value,
+                    &row.ipv4_range.map(|range| i64::from(range.0)),
+                    &row.ipv4_range.map(|range| i64::from(range.1)),
+                    &row.reversed_value,
+                ])?;
+            }
+        }
+    }
+
+    // Creating indexes after filling tables is faster.
+    transaction.execute_batch(INDEXES)?;
+    transaction.commit()?;
+    Ok(())
+}
+
+/// Write SQLite database.
+///
+/// Database is created in temporary file and then copied into writer. Column "reversed_value" contains reversed domain
+/// names for queries by suffix, "ipv4_start" and "ipv4_end" contain the first and the last IPv4 address as integer.
+pub fn export<StreamWriter>(list: &super::List, writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let database_file = tempfile::NamedTempFile::new()?;
+    {
+        let mut connection = rusqlite::Connection::open(database_file.path())?;
+        write_database(&mut connection, list)?;
+    }
+
+    let _ = std::io::copy(&mut database_file.reopen()?, writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite;
+    use tempfile;
+
+    use super::super::tests;
+
+    fn query(connection: &rusqlite::Connection, sql: &str) -> Vec<String> {
+        let mut statement = connection.prepare(sql).unwrap();
+        let rows = statement
+            .query_map(rusqlite::NO_PARAMS, |row| row.get::<_, String>(0))
+            .unwrap();
+        rows.map(Result::unwrap).collect()
+    }
+
+    #[test]
+    fn export() {
+        use std::io::Write;
+
+        // Database is not valid UTF-8, so `tests::export()` is not usable.
+        let mut output = Vec::new();
+        super::export(&tests::list(), &mut output).unwrap();
+        let mut database_file = tempfile::NamedTempFile::new().unwrap();
+        database_file.write_all(&output).unwrap();
+        let connection = rusqlite::Connection::open(database_file.path()).unwrap();
+
+        assert_eq!(query(&connection, "SELECT updated FROM list"), vec!["2017-11-29 13:34:56"]);
+        assert_eq!(
+            query(
+                &connection,
+                "SELECT id || ' ' || organization || ' ' || document_id || ' ' || document_date FROM records",
+            ),
+            vec![
+                "1 Org 1 1-2-3 2017-01-02",
+                "2 Org 2 4-5-6 2017-01-02",
+                "3 Org 1 7-8-9 2017-01-02",
+                "4 Org 3 10 2017-01-02",
+            ]
+        );
+        assert_eq!(
+            query(
+                &connection,
+                "SELECT record_id || ' ' || kind || ' ' || value FROM addresses ORDER BY record_id, kind, value",
+            ),
+            vec![
+                "1 DomainName example.com",
+                "1 IPv4 1.2.3.4",
+                "1 URL http://example.com/",
+                "2 IPv4 4.3.2.1",
+                "2 IPv4Network 1.2.3.0/24",
+                "2 WildcardDomainName *.example.org",
+                "3 IPv4 1.2.3.4",
+                "3 IPv4Network 10.20.30.41/30",
+                "3 URL http://example.net/path?a=b",
+                "4 DomainName xn--e1aybc.org",
+                "4 URL http://xn--e1aybc.org:8080/test",
+                "4 WildcardDomainName *",
+            ]
+        );
+
+        // Blocked ranges which contain 1.2.3.4.
+        assert_eq!(
+            query(
+                &connection,
+                "SELECT value FROM addresses WHERE ipv4_start <= 16909060 AND ipv4_end >= 16909060 ORDER BY value",
+            ),
+            vec!["1.2.3.0/24", "1.2.3.4", "1.2.3.4"]
+        );
+        assert_eq!(
+            query(&connection, "SELECT ipv4_start || '-' || ipv4_end FROM addresses WHERE value = '10.20.30.41/30'"),
+            vec!["169090600-169090603"]
+        );
+
+        // Domain names in ".org" zone.
+        assert_eq!(
+            query(
+                &connection,
+                "SELECT value FROM addresses WHERE reversed_value >= 'gro.' AND reversed_value < 'gro/' ORDER BY value",
+            ),
+            vec!["*.example.org", "xn--e1aybc.org"]
+        );
+    }
+}
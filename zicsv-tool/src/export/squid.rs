@@ -0,0 +1,151 @@
+use std;
+
+use failure;
+
+use zicsv;
+
+/// Values of "dstdomain" ACL.
+///
+/// Squid is unable to match only subdomains, so wildcard domain name blocks its base domain name too. Names covered by
+/// wildcards are skipped because Squid complains about them.
+fn dstdomain_values(list: &super::List) -> Vec<String> {
+    let domains = list.domains_without_urls();
+    let wildcards: std::collections::BTreeSet<&str> = domains
+        .iter()
+        .filter(|domain| domain.wildcard)
+        .map(|domain| domain.name.as_str())
+        .collect();
+
+    let is_covered_by_wildcard = |name: &str| {
+        name.match_indices('.')
+            .any(|(pos, _)| wildcards.contains(&name[pos + 1..]))
+    };
+
+    let mut values: Vec<String> = domains
+        .iter()
+        .filter(|domain| !is_covered_by_wildcard(&domain.name))
+        .map(|domain| {
+            if wildcards.contains(domain.name.as_str()) {
+                format!(".{}", domain.name)
+            } else {
+                domain.name.clone()
+            }
+        })
+        .collect();
+    // Exact and wildcard domain names with the same base are adjacent.
+    values.dedup();
+    values
+}
+
+/// Values of "dst" ACL.
+fn dst_values(list: &super::List) -> Vec<String> {
+    let networks = zicsv::aggregate_ipv4(list.addresses().filter(|address| match **address {
+        zicsv::Address::IPv4(_) | zicsv::Address::IPv4Network(_) => true,
+        _ => false,
+    }));
+
+    networks
+        .iter()
+        .map(|network| {
+            if network.prefix_len() == 32 {
+                network.addr().to_string()
+            } else {
+                super::format_ipv4_network(network)
+            }
+        })
+        .collect()
+}
+
+/// Values of "url_regex" ACL, each matches exactly one URL.
+fn url_regex_values(list: &super::List) -> Vec<String> {
+    list.urls().iter().map(|url| format!("^{}$", super::escape_regex(url))).collect()
+}
+
+fn write_acl<StreamWriter>(
+    name: &str,
+    acl_type: &str,
+    values: &[String],
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    // Squid appends values of ACL declared several times, this keeps lines short.
+    for value in values {
+        writeln!(writer, "acl {} {} {}", name, acl_type, value)?;
+    }
+    Ok(())
+}
+
+/// Write ACLs for Squid.
+///
+/// Domain names, IPv4 addresses and URLs are declared as "{prefix}_domains", "{prefix}_dst" and "{prefix}_urls" ACLs.
+/// Blocked URLs do not block their hosts. Empty ACLs are not declared.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    write_acl(
+        &format!("{}_domains", options.squid_acl_prefix),
+        "dstdomain",
+        &dstdomain_values(list),
+        writer,
+    )?;
+    write_acl(
+        &format!("{}_dst", options.squid_acl_prefix),
+        "dst",
+        &dst_values(list),
+        writer,
+    )?;
+    write_acl(
+        &format!("{}_urls", options.squid_acl_prefix),
+        "url_regex",
+        &url_regex_values(list),
+        writer,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/squid.conf")
+        );
+    }
+
+    #[test]
+    fn export_wildcards() {
+        let list = super::super::List {
+            records: tests::records(
+                ";example.com | *.example.com | test.example.com | *.test.example.com | example.net;\
+                 http://test.example.net/test#fragment;Org 1;1-2-3;2017-01-02\n",
+            ),
+
+            ..tests::list()
+        };
+
+        let mut output = Vec::new();
+        super::export(&tests::options(), &list, &mut output).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\
+             # Generated by zicsv-tool from list updated at 2017-11-29 13:34:56 UTC.\n\
+             acl zicsv_domains dstdomain .example.com\n\
+             acl zicsv_domains dstdomain example.net\n\
+             acl zicsv_urls url_regex ^http://test\\.example\\.net/test$\n\
+             "
+        );
+    }
+}
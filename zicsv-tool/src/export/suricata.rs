@@ -0,0 +1,245 @@
+use std;
+
+use failure;
+use url;
+
+use zicsv;
+
+/// SIDs are allocated from range `[sid_base, sid_base + SID_RANGE)`.
+const SID_RANGE: u32 = 1 << 28;
+
+/// 32-bit FNV-1a hash. Unlike hashers from standard library, it is guaranteed to be stable.
+fn fnv1a(data: &[u8]) -> u32 {
+    data.iter()
+        .fold(0x811c_9dc5u32, |hash, byte| (hash ^ u32::from(*byte)).wrapping_mul(0x0100_0193))
+}
+
+/// Offset of SID from hash of rule key. Non-zero attempt is used on collision with SID of another rule.
+fn sid_offset(key: &str, attempt: u32) -> u32 {
+    if attempt == 0 {
+        fnv1a(key.as_bytes()) % SID_RANGE
+    } else {
+        fnv1a(format!("{}#{}", key, attempt).as_bytes()) % SID_RANGE
+    }
+}
+
+/// Escape string for "msg" option.
+pub fn escape_msg(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if c == '"' || c == ';' || c == '\\' {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Escape string for "content" option, special and non-printable bytes are written in hexadecimal form.
+pub fn escape_content(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'"' | b';' | b'\\' | b'|' => escaped.push_str(&format!("|{:02X}|", byte)),
+            0x20...0x7e => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("|{:02X}|", byte)),
+        }
+    }
+    escaped
+}
+
+/// Rule keyed by "protocol:blocked address" string which is also used for SID.
+struct Rule<'a> {
+    key: String,
+    protocol: &'static str,
+    description: String,
+    /// Pairs of sticky buffer and its options.
+    matches: Vec<(&'static str, String)>,
+    records: Vec<&'a zicsv::Record>,
+}
+
+/// Match exact string or string which ends with given suffix.
+fn match_options(content: &str, exact: bool) -> String {
+    if exact {
+        format!("content:\"{}\"; bsize:{};", escape_content(content), content.len())
+    } else {
+        format!("content:\"{}\"; endswith;", escape_content(content))
+    }
+}
+
+/// Path and query of URL as seen in normalized URI buffer of Suricata: percent-encoded characters are decoded.
+fn http_uri(url: &url::Url) -> String {
+    let mut uri = url.path().to_string();
+    if let Some(query) = url.query() {
+        uri.push('?');
+        uri.push_str(query);
+    }
+    url::percent_encoding::percent_decode(uri.as_bytes())
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+fn url_rules(list: &super::List) -> Vec<Rule> {
+    let mut urls = std::collections::BTreeMap::new();
+    for record in &list.records {
+        for address in &record.addresses {
+            if let zicsv::Address::URL(ref url) = *address {
+                let host = match url.host() {
+                    Some(url::Host::Domain(name)) => name.trim_right_matches('.').to_string(),
+                    Some(host) => host.to_string(),
+                    None => continue,
+                };
+                let uri = http_uri(url);
+
+                let records = urls.entry((host, uri)).or_insert_with(Vec::new);
+                if !records.contains(&record) {
+                    records.push(record);
+                }
+            }
+        }
+    }
+
+    urls.into_iter()
+        .map(|((host, uri), records)| Rule {
+            key: format!("http:{}{}", host, uri),
+            protocol: "http",
+            description: format!("{}{}", host, uri),
+            matches: vec![
+                ("http.host", match_options(&host, true)),
+                ("http.uri", match_options(&uri, true)),
+            ],
+            records,
+        })
+        .collect()
+}
+
+fn domain_rules(list: &super::List) -> Vec<Rule> {
+    let mut rules = Vec::new();
+    for domain in list.domains_without_urls() {
+        let (description, content) = if domain.wildcard {
+            (format!("*.{}", domain.name), format!(".{}", domain.name))
+        } else {
+            (domain.name.clone(), domain.name.clone())
+        };
+
+        for &(protocol, buffer) in &[("tls", "tls.sni"), ("dns", "dns.query")] {
+            rules.push(Rule {
+                key: format!("{}:{}", protocol, description),
+                protocol,
+                description: description.clone(),
+                matches: vec![(buffer, format!("{} nocase;", match_options(&content, !domain.wildcard)))],
+                records: domain.records.clone(),
+            });
+        }
+    }
+    rules
+}
+
+/// Write rules for Suricata.
+///
+/// URLs are matched by exact host and normalized URI of HTTP request, domain names are matched by TLS SNI and DNS
+/// query. SID of rule is derived from hash of its own key (protocol and blocked address), so it does not change
+/// between lists. Collisions are resolved in order of keys: rule which collides with a rule with smaller key gets SID
+/// from hash of "key#1", "key#2" and so on until free SID is found.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    ensure!(
+        u64::from(options.suricata_sid_base) + u64::from(SID_RANGE) <= u64::from(std::u32::MAX) + 1,
+        "SID base should not be greater than {}",
+        u64::from(std::u32::MAX) + 1 - u64::from(SID_RANGE)
+    );
+
+    let mut rules = url_rules(list);
+    rules.extend(domain_rules(list));
+    rules.sort_by(|a, b| a.key.cmp(&b.key));
+
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    let mut used_sids = std::collections::BTreeSet::new();
+    for rule in rules {
+        let offset = (0..)
+            .map(|attempt| sid_offset(&rule.key, attempt))
+            .find(|offset| !used_sids.contains(offset))
+            .expect("Number of rules is less than number of SIDs");
+        let _ = used_sids.insert(offset);
+
+        let mut document_ids: Vec<&str> = rule.records
+            .iter()
+            .map(|record| record.document_id.as_str())
+            .collect();
+        document_ids.sort();
+        document_ids.dedup();
+        let msg = format!("zicsv: {} blocked by {}", rule.description, document_ids.join(", "));
+
+        let matches: Vec<_> = rule.matches
+            .iter()
+            .map(|&(buffer, ref options)| format!("{}; {}", buffer, options))
+            .collect();
+
+        writeln!(
+            writer,
+            "alert {} any any -> any any (msg:\"{}\"; {} sid:{}; rev:1;)",
+            rule.protocol,
+            escape_msg(&msg),
+            matches.join(" "),
+            options.suricata_sid_base + offset
+        )?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn fnv1a() {
+        assert_eq!(super::fnv1a(b""), 0x811c_9dc5);
+        assert_eq!(super::fnv1a(b"a"), 0xe40c_292c);
+        assert_eq!(super::fnv1a(b"foobar"), 0xbf9c_f968);
+    }
+
+    #[test]
+    fn sid_offset() {
+        assert_eq!(super::sid_offset("dns:example.com", 0), super::fnv1a(b"dns:example.com") % super::SID_RANGE);
+        assert_eq!(
+            super::sid_offset("dns:example.com", 2),
+            super::fnv1a(b"dns:example.com#2") % super::SID_RANGE
+        );
+    }
+
+    #[test]
+    fn http_uri() {
+        assert_eq!(
+            super::http_uri(&"http://example.com/%D1%82%D0%B5%D1%81%D1%82?a=%20b".parse().unwrap()),
+            "/\u{442}\u{435}\u{441}\u{442}?a= b"
+        );
+        assert_eq!(super::http_uri(&"http://example.com".parse().unwrap()), "/");
+    }
+
+    #[test]
+    fn escape() {
+        assert_eq!(super::escape_msg("a \"b\"; c\\"), "a \\\"b\\\"\\; c\\\\");
+        assert_eq!(
+            super::escape_content("/a\"b;c\\d|e\u{418}"),
+            "/a|22|b|3B|c|5C|d|7C|e|D0||98|"
+        );
+    }
+
+    #[test]
+    fn export() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/suricata.rules")
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std;
+
+use failure;
+use ipnet;
+use tera;
+use url;
+
+use zicsv;
+
+type FilterArgs = std::collections::HashMap<String, tera::Value>;
+
+fn filter_input<'a>(filter: &str, value: &'a tera::Value) -> tera::Result<&'a str> {
+    value
+        .as_str()
+        .ok_or_else(|| tera::Error::msg(format!("Filter \"{}\" expects string, got: {}", filter, value)))
+}
+
+/// Filter which converts IPv4 address or network into "address/prefix length" with host bits cleared.
+fn cidr_filter(value: &tera::Value, _args: &FilterArgs) -> tera::Result<tera::Value> {
+    let value = filter_input("cidr", value)?;
+    let network = if value.contains('/') {
+        value.parse::<ipnet::Ipv4Net>().ok()
+    } else {
+        value
+            .parse::<std::net::Ipv4Addr>()
+            .ok()
+            .map(super::ipv4_host_network)
+    };
+
+    match network {
+        Some(network) => Ok(tera::Value::String(super::format_ipv4_network(&network.trunc()))),
+
+        None => Err(tera::Error::msg(format!(
+            "Invalid IPv4 address or network: \"{}\"",
+            value
+        ))),
+    }
+}
+
+/// Filter which converts internationalized domain name from punycode into unicode.
+fn punycode_to_unicode_filter(value: &tera::Value, _args: &FilterArgs) -> tera::Result<tera::Value> {
+    let value = filter_input("punycode_to_unicode", value)?;
+    match url::idna::domain_to_unicode(value) {
+        (unicode, Ok(())) => Ok(tera::Value::String(unicode)),
+        (_, Err(_)) => Err(tera::Error::msg(format!("Invalid domain name: \"{}\"", value))),
+    }
+}
+
+/// Filter which escapes special characters of POSIX extended regular expressions.
+fn regex_escape_filter(value: &tera::Value, _args: &FilterArgs) -> tera::Result<tera::Value> {
+    Ok(tera::Value::String(super::escape_regex(filter_input("regex_escape", value)?)))
+}
+
+/// Data available in template.
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    /// Date and time of last update of list, UTC.
+    updated: String,
+    records: &'a [zicsv::Record],
+    ipv4_addresses: Vec<String>,
+    ipv4_networks: Vec<String>,
+    domains: Vec<String>,
+    /// Wildcard domain names without "*." prefix.
+    wildcard_domains: Vec<String>,
+    urls: Vec<String>,
+}
+
+fn render(template: &str, list: &super::List) -> Result<String, failure::Error> {
+    const TEMPLATE_NAME: &str = "export";
+
+    let mut tera = tera::Tera::default();
+    tera.add_raw_template(TEMPLATE_NAME, template)?;
+    tera.register_filter("cidr", cidr_filter);
+    tera.register_filter("punycode_to_unicode", punycode_to_unicode_filter);
+    tera.register_filter("regex_escape", regex_escape_filter);
+
+    let mut domains = Vec::new();
+    let mut wildcard_domains = Vec::new();
+    for domain in list.domains_without_urls() {
+        if domain.wildcard {
+            wildcard_domains.push(domain.name);
+        } else {
+            domains.push(domain.name);
+        }
+    }
+
+    let context = TemplateContext {
+        updated: list.updated.to_string(),
+        records: &list.records,
+        ipv4_addresses: list.ipv4_addresses().iter().map(ToString::to_string).collect(),
+        ipv4_networks: list.ipv4_networks().iter().map(super::format_ipv4_network).collect(),
+        domains,
+        wildcard_domains,
+        urls: list.urls().into_iter().collect(),
+    };
+
+    Ok(tera.render(TEMPLATE_NAME, &tera::Context::from_serialize(&context)?)?)
+}
+
+/// Render list using Tera template.
+///
+/// Besides list itself, template gets sorted unique addresses of each type and filters "cidr", "punycode_to_unicode"
+/// and "regex_escape".
+pub fn export<StreamWriter>(
+    template_path: &str,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let template = std::fs::read_to_string(template_path)
+        .map_err(|error| failure::Error::from(error).context(format!("File: \"{}\"", template_path)))?;
+
+    writer.write_all(render(&template, list)?.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            super::render(include_str!("testdata/template.tera"), &tests::list()).unwrap(),
+            include_str!("testdata/template.txt")
+        );
+    }
+
+    #[test]
+    fn invalid_filter_input() {
+        let list = tests::list();
+
+        assert!(super::render("{{ \"1.2.3\" | cidr }}", &list).is_err());
+        assert!(super::render("{{ \"xn--a.org\" | punycode_to_unicode }}", &list).is_err());
+        assert!(super::render("{{ updated | nonexistent }}", &list).is_err());
+    }
+}
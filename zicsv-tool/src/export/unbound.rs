@@ -0,0 +1,58 @@
+use std;
+
+use failure;
+
+/// Write configuration for Unbound with local zones.
+///
+/// Local zone covers the domain name and all its subdomains, so exact and wildcard domain names produce the same zone.
+pub fn export<StreamWriter>(
+    options: &super::ExportOptions,
+    list: &super::List,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+    writeln!(writer, "server:")?;
+
+    for name in list.domain_subtrees() {
+        if let Some(redirect) = options.redirect {
+            writeln!(writer, "local-zone: \"{}.\" redirect", name)?;
+            writeln!(writer, "local-data: \"{}. A {}\"", name, redirect)?;
+        } else {
+            writeln!(writer, "local-zone: \"{}.\" always_nxdomain", name)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export_nxdomain() {
+        let options = tests::options();
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/unbound-nxdomain.conf")
+        );
+    }
+
+    #[test]
+    fn export_redirect() {
+        let options = super::super::ExportOptions {
+            redirect: Some("10.0.0.1".parse().unwrap()),
+
+            ..tests::options()
+        };
+
+        assert_eq!(
+            tests::export(|list, output| super::export(&options, list, output)),
+            include_str!("testdata/unbound-redirect.conf")
+        );
+    }
+}
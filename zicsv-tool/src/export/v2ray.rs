@@ -0,0 +1,45 @@
+use std;
+
+use failure;
+
+/// Write domain list in format of v2ray geosite sources.
+///
+/// Format contains only domain names, IPv4 addresses and networks are skipped with a warning. Wildcard domain name is
+/// represented by "domain" rule, which also matches its base domain name.
+pub fn export<StreamWriter>(list: &super::List, writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writeln!(writer, "# Generated by zicsv-tool from list updated at {} UTC.", list.updated)?;
+
+    for domain in list.domains() {
+        if domain.wildcard {
+            writeln!(writer, "domain:{}", domain.name)?;
+        } else {
+            writeln!(writer, "full:{}", domain.name)?;
+        }
+    }
+
+    let n_ipv4 = list.ipv4_addresses().len() + list.ipv4_networks().len();
+    if n_ipv4 > 0 {
+        eprintln!(
+            "Warning: {} IPv4 addresses and networks are skipped, v2ray geosite contains only domain names",
+            n_ipv4
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::tests;
+
+    #[test]
+    fn export() {
+        assert_eq!(
+            tests::export(|list, output| super::export(list, output)),
+            include_str!("testdata/v2ray.txt")
+        );
+    }
+}
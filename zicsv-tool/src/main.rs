@@ -16,40 +16,104 @@
 #[macro_use]
 extern crate failure;
 
+#[cfg(feature = "parquet-export")]
+extern crate arrow_array;
+#[cfg(feature = "parquet-export")]
+extern crate arrow_schema;
 extern crate ipnet;
+#[cfg(feature = "parquet-export")]
+extern crate parquet;
+#[cfg(feature = "msgpack")]
+extern crate rmp_serde;
 
 extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 
+#[cfg(feature = "cbor")]
+extern crate serde_cbor;
 extern crate serde_json;
+#[cfg(feature = "yaml")]
+extern crate serde_yaml;
 
 extern crate structopt;
 #[macro_use]
 extern crate structopt_derive;
 
+#[cfg(feature = "sqlite")]
+extern crate rusqlite;
+#[cfg(any(test, feature = "sqlite"))]
+extern crate tempfile;
+#[cfg(feature = "template")]
+extern crate tera;
+#[cfg(feature = "toml")]
+extern crate toml;
 extern crate trust_dns_proto;
 extern crate trust_dns_resolver;
 extern crate url;
 
 extern crate zicsv;
 
-mod into_json;
+#[cfg(all(test, feature = "schema-tests"))]
+extern crate jsonschema;
+#[cfg(all(test, feature = "pac-tests"))]
+extern crate quick_js;
+
+mod aggregate;
+mod convert;
+mod export;
+mod normalize;
 mod print_err;
+mod routes;
+mod schema;
 mod search;
 mod select;
+mod serialize;
 
 #[derive(Debug)]
 pub enum OutputFormat {
     HumanReadable,
     PrettyJSON,
     JSON,
+    #[cfg(feature = "yaml")]
+    YAML,
+    #[cfg(feature = "toml")]
+    TOML,
+    #[cfg(feature = "cbor")]
+    CBOR,
+    #[cfg(feature = "msgpack")]
+    MessagePack,
 }
 
 // TODO: Support different output formats for "select" and "updated".
 impl OutputFormat {
     fn variants() -> Vec<&'static str> {
-        vec!["human-readable", "pretty-json", "json"]
+        let mut variants = vec!["human-readable"];
+        variants.extend(Self::serialization_variants());
+        variants
+    }
+
+    /// Formats supported by serde.
+    fn serialization_variants() -> Vec<&'static str> {
+        let mut variants = vec!["pretty-json", "json"];
+        if cfg!(feature = "yaml") {
+            variants.push("yaml");
+        }
+        if cfg!(feature = "toml") {
+            variants.push("toml");
+        }
+        if cfg!(feature = "cbor") {
+            variants.push("cbor");
+        }
+        if cfg!(feature = "msgpack") {
+            variants.push("msgpack");
+        }
+        variants
+    }
+
+    /// Formats of search results. TOML is not supported because TOML document is a table, not a list.
+    fn search_variants() -> Vec<&'static str> {
+        Self::variants().into_iter().filter(|variant| *variant != "toml").collect()
     }
 }
 
@@ -61,6 +125,14 @@ impl std::str::FromStr for OutputFormat {
             "human-readable" => Ok(OutputFormat::HumanReadable),
             "pretty-json" => Ok(OutputFormat::PrettyJSON),
             "json" => Ok(OutputFormat::JSON),
+            #[cfg(feature = "yaml")]
+            "yaml" => Ok(OutputFormat::YAML),
+            #[cfg(feature = "toml")]
+            "toml" => Ok(OutputFormat::TOML),
+            #[cfg(feature = "cbor")]
+            "cbor" => Ok(OutputFormat::CBOR),
+            #[cfg(feature = "msgpack")]
+            "msgpack" => Ok(OutputFormat::MessagePack),
 
             unknown_format => Err(format_err!("Unknown output format: \"{}\"", unknown_format)),
         }
@@ -76,6 +148,14 @@ impl std::fmt::Display for OutputFormat {
                 OutputFormat::HumanReadable => "human-readable",
                 OutputFormat::PrettyJSON => "pretty-json",
                 OutputFormat::JSON => "json",
+                #[cfg(feature = "yaml")]
+                OutputFormat::YAML => "yaml",
+                #[cfg(feature = "toml")]
+                OutputFormat::TOML => "toml",
+                #[cfg(feature = "cbor")]
+                OutputFormat::CBOR => "cbor",
+                #[cfg(feature = "msgpack")]
+                OutputFormat::MessagePack => "msgpack",
             }
         )
     }
@@ -83,10 +163,281 @@ impl std::fmt::Display for OutputFormat {
 
 #[derive(StructOpt, Debug)]
 enum Command {
-    #[structopt(name = "into-json", about = "Convert into json format")]
-    IntoJson {
-        #[structopt(short = "P", long = "disable-pretty", help = "Disable pretty-printing")]
+    #[structopt(name = "aggregate", about = "Print blocked IPv4 addresses and networks as minimal set of networks")]
+    Aggregate {
+        #[structopt(
+            name = "N",
+            short = "m",
+            long = "max-prefixes",
+            help = "Merge neighbouring networks into wider ones until number of networks is not greater than N"
+        )]
+        max_prefixes: Option<usize>,
+
+        #[structopt(
+            long = "stats",
+            raw(requires = "\"N\""),
+            help = "Print number of networks, blocked and over-blocked addresses to stderr"
+        )]
+        stats: bool,
+    },
+
+    #[structopt(name = "export", about = "Export blocked addresses into configuration of other software")]
+    Export {
+        #[structopt(
+            name = "FORMAT",
+            short = "f",
+            long = "format",
+            raw(
+                possible_values = "&export::ExportFormat::variants()",
+                required_unless = "\"TEMPLATE\"",
+                conflicts_with = "\"TEMPLATE\""
+            )
+        )]
+        format: Option<export::ExportFormat>,
+
+        #[structopt(
+            name = "TEMPLATE",
+            long = "template",
+            help = "Export using Tera template instead of one of predefined formats"
+        )]
+        template_path: Option<String>,
+
+        #[structopt(
+            name = "PREVIOUS",
+            long = "previous",
+            help = "Previous version of list, export only changes (supported by ipset and nftables formats)"
+        )]
+        previous_path: Option<String>,
+
+        #[structopt(
+            name = "REDIRECT",
+            long = "redirect",
+            help = "Resolve blocked domain names into address of block page instead of NXDOMAIN (DNS formats and hosts)"
+        )]
+        redirect: Option<std::net::Ipv4Addr>,
+
+        #[structopt(
+            name = "IPSET IP SET",
+            long = "ipset-ip-set",
+            default_value = "zicsv-ip",
+            help = "Name of ipset for IPv4 addresses"
+        )]
+        ipset_ip_set: String,
+
+        #[structopt(
+            name = "IPSET NET SET",
+            long = "ipset-net-set",
+            default_value = "zicsv-net",
+            help = "Name of ipset for IPv4 networks"
+        )]
+        ipset_net_set: String,
+
+        #[structopt(
+            name = "NFT FAMILY",
+            long = "nft-family",
+            default_value = "inet",
+            help = "Family of nftables table"
+        )]
+        nft_family: String,
+
+        #[structopt(
+            name = "NFT TABLE",
+            long = "nft-table",
+            default_value = "zicsv",
+            help = "Name of nftables table"
+        )]
+        nft_table: String,
+
+        #[structopt(
+            name = "NFT IP SET",
+            long = "nft-ip-set",
+            default_value = "zicsv_ip",
+            help = "Name of nftables set for IPv4 addresses"
+        )]
+        nft_ip_set: String,
+
+        #[structopt(
+            name = "NFT NET SET",
+            long = "nft-net-set",
+            default_value = "zicsv_net",
+            help = "Name of nftables set for IPv4 networks"
+        )]
+        nft_net_set: String,
+
+        #[structopt(
+            name = "RPZ NS",
+            long = "rpz-ns",
+            default_value = "localhost.",
+            help = "Name server of response policy zone"
+        )]
+        rpz_ns: String,
+
+        #[structopt(
+            name = "DNSMASQ NFTSET",
+            long = "dnsmasq-nftset",
+            help = "Add resolved addresses into nftables set (\"family#table#set\") instead of blocking resolution"
+        )]
+        dnsmasq_nftset: Option<String>,
+
+        #[structopt(
+            name = "PAC PROXY",
+            long = "pac-proxy",
+            default_value = "PROXY localhost:3128",
+            help = "Value returned by proxy auto-config for blocked hosts"
+        )]
+        pac_proxy: String,
+
+        #[structopt(
+            long = "pac-resolve",
+            help = "Resolve host names in proxy auto-config to check blocked IPv4 addresses (slow)"
+        )]
+        pac_resolve: bool,
+
+        #[structopt(
+            name = "SQUID ACL PREFIX",
+            long = "squid-acl-prefix",
+            default_value = "zicsv",
+            help = "Prefix of names of Squid ACLs"
+        )]
+        squid_acl_prefix: String,
+
+        #[structopt(
+            name = "ROUTEROS LIST",
+            long = "routeros-list",
+            default_value = "zicsv",
+            help = "Name of RouterOS firewall address list"
+        )]
+        routeros_list: String,
+
+        #[structopt(
+            name = "ROUTEROS TAG",
+            long = "routeros-tag",
+            default_value = "zicsv",
+            help = "Prefix of comments which marks RouterOS address list entries managed by script"
+        )]
+        routeros_tag: String,
+
+        #[structopt(
+            name = "ROUTEROS TIMEOUT",
+            long = "routeros-timeout",
+            help = "Timeout of RouterOS address list entries (\"1d\", \"12:00:00\" etc.)"
+        )]
+        routeros_timeout: Option<String>,
+
+        #[structopt(long = "routeros-domains", help = "Add blocked domain names into RouterOS address list")]
+        routeros_domains: bool,
+
+        #[structopt(
+            name = "NEXT HOP",
+            long = "next-hop",
+            help = "Next hop of routes, blackhole routes are exported if not specified (bird and frr formats)"
+        )]
+        next_hop: Option<std::net::Ipv4Addr>,
+
+        #[structopt(
+            name = "COMMUNITY",
+            long = "community",
+            help = "BGP community (\"ASN:value\") or large community (\"ASN:value:value\") of routes"
+        )]
+        communities: Vec<export::Community>,
+
+        #[structopt(long = "route-aggregate", help = "Aggregate routes into minimal set of networks")]
+        route_aggregate: bool,
+
+        #[structopt(
+            name = "ROUTE NAME",
+            long = "route-name",
+            default_value = "zicsv",
+            help = "Name of BIRD static protocol or FRR prefix list and route map"
+        )]
+        route_name: String,
+
+        #[structopt(
+            name = "SURICATA SID BASE",
+            long = "suricata-sid-base",
+            default_value = "1000000000",
+            help = "First SID of Suricata rules"
+        )]
+        suricata_sid_base: u32,
+
+        #[structopt(
+            name = "CSV DELIMITER",
+            long = "csv-delimiter",
+            help = "Field delimiter of csv and tsv formats (default is comma or tab)"
+        )]
+        csv_delimiter: Option<char>,
+    },
+
+    #[structopt(
+        name = "convert",
+        about = "Convert into json or other machine-readable format",
+        raw(alias = "\"into-json\"")
+    )]
+    Convert {
+        #[structopt(
+            name = "OUTPUT FORMAT",
+            short = "O",
+            long = "output-format",
+            help = "Output format, pretty-printed JSON by default",
+            raw(
+                possible_values = "&OutputFormat::serialization_variants()",
+                conflicts_with_all = "&[\"DISABLE PRETTY\", \"NDJSON\"]"
+            )
+        )]
+        output_format: Option<OutputFormat>,
+
+        #[structopt(
+            name = "DISABLE PRETTY",
+            short = "P",
+            long = "disable-pretty",
+            help = "Disable pretty-printing of JSON",
+            raw(conflicts_with = "\"NDJSON\"")
+        )]
         disable_pretty: bool,
+
+        #[structopt(
+            name = "NDJSON",
+            long = "ndjson",
+            help = "Write one JSON record per line, date of last update is written as the first line"
+        )]
+        ndjson: bool,
+
+        #[structopt(
+            long = "normalized",
+            help = "Write organizations, documents and addresses as separate lists cross-referenced by ids",
+            raw(conflicts_with = "\"NDJSON\"")
+        )]
+        normalized: bool,
+
+        #[structopt(
+            long = "per-address",
+            help = "Write one address per line (with --ndjson)",
+            raw(requires = "\"NDJSON\"")
+        )]
+        per_address: bool,
+
+        #[structopt(
+            long = "updated-field",
+            help = "Add date of last update into each line instead of the first line (with --ndjson)",
+            raw(requires = "\"NDJSON\"")
+        )]
+        updated_field: bool,
+    },
+
+    #[structopt(name = "normalize", about = "Sort, deduplicate and merge records, write result in CSV format")]
+    Normalize,
+
+    #[structopt(name = "routes", about = "Print blocked IPv4 space as minimal set of networks for routing")]
+    Routes {
+        #[structopt(short = "c", long = "complement", help = "Print all IPv4 space except blocked addresses")]
+        complement: bool,
+
+        #[structopt(
+            short = "r",
+            long = "exclude-reserved",
+            help = "Exclude private, loopback, multicast and other special-purpose networks"
+        )]
+        exclude_reserved: bool,
     },
 
     #[structopt(name = "select", about = "Print selected types of blocked addresses")]
@@ -105,11 +456,21 @@ enum Command {
 
         #[structopt(short = "u", long = "url", help = "URLs")]
         url: bool,
+
+        #[structopt(
+            short = "a",
+            long = "aggregate",
+            help = "Collapse selected IPv4 addresses and networks into minimal set of networks"
+        )]
+        aggregate: bool,
     },
 
     #[structopt(name = "updated", about = "Print date of last update")]
     Updated,
 
+    #[structopt(name = "schema", about = "Print JSON Schema of JSON output of \"convert\" and \"search\"")]
+    Schema,
+
     #[structopt(name = "search", about = "Search blocked addresses")]
     Search {
         #[structopt(
@@ -117,7 +478,7 @@ enum Command {
             short = "O",
             long = "output-format",
             default_value = "human-readable",
-            raw(possible_values = "&OutputFormat::variants()")
+            raw(possible_values = "&OutputFormat::search_variants()")
         )]
         output_format: OutputFormat,
 
@@ -138,8 +499,8 @@ struct Options {
     command: Command,
 }
 
-fn create_reader(options: &Options) -> Result<Box<zicsv::GenericReader>, failure::Error> {
-    Ok(if let Some(input_path) = options.input_path.as_ref() {
+fn create_reader(input_path: Option<&str>) -> Result<Box<zicsv::GenericReader>, failure::Error> {
+    Ok(if let Some(input_path) = input_path {
         Box::new(zicsv::Reader::from_file(input_path)?)
     } else {
         Box::new(zicsv::Reader::from_reader(std::io::stdin())?)
@@ -167,13 +528,134 @@ fn real_main() -> Result<(), failure::Error> {
     // TODO: Also generate auto-completion scripts.
     let options = Options::from_args();
 
-    let reader = create_reader(&options)?;
-
     let mut stdout = std::io::stdout();
+
     let mut writer = create_writer(&options, &mut stdout)?;
+    // List is read only by commands which need it.
+    let input_path = options.input_path.as_ref().map(String::as_str);
 
     match options.command {
-        Command::IntoJson { disable_pretty } => into_json::into_json(reader, &mut writer, disable_pretty)?,
+        Command::Aggregate { max_prefixes, stats } => {
+            ensure!(max_prefixes != Some(0), "Maximum number of prefixes should be greater than zero");
+
+            aggregate::aggregate(max_prefixes, stats, create_reader(input_path)?, &mut writer)?
+        },
+
+        Command::Export {
+            format,
+            template_path,
+            previous_path,
+            redirect,
+            ipset_ip_set,
+            ipset_net_set,
+            nft_family,
+            nft_table,
+            nft_ip_set,
+            nft_net_set,
+            rpz_ns,
+            dnsmasq_nftset,
+            pac_proxy,
+            pac_resolve,
+            squid_acl_prefix,
+            routeros_list,
+            routeros_tag,
+            routeros_timeout,
+            routeros_domains,
+            next_hop,
+            communities,
+            route_aggregate,
+            route_name,
+            suricata_sid_base,
+            csv_delimiter,
+        } => {
+            let eopts = export::ExportOptions {
+                format: match (format, template_path) {
+                    (Some(format), _) => format,
+                    (None, Some(template_path)) => export::ExportFormat::template(template_path)?,
+                    // Argument parser ensures that either format or template is specified.
+                    (None, None) => bail!("Neither format nor template is specified"),
+                },
+                previous_path,
+                redirect,
+
+                ipset_ip_set,
+                ipset_net_set,
+
+                nft_family,
+                nft_table,
+                nft_ip_set,
+                nft_net_set,
+
+                rpz_ns,
+
+                dnsmasq_nftset,
+
+                pac_proxy,
+                pac_resolve,
+
+                squid_acl_prefix,
+
+                routeros_list,
+                routeros_tag,
+                routeros_timeout,
+                routeros_domains,
+
+                next_hop,
+                communities,
+                route_aggregate,
+                route_name,
+
+                suricata_sid_base,
+
+                csv_delimiter,
+            };
+
+            export::export(&eopts, create_reader(input_path)?, &mut writer)?
+        },
+
+        Command::Convert {
+            output_format,
+            disable_pretty,
+            ndjson,
+            normalized,
+            per_address,
+            updated_field,
+        } => {
+            if ndjson {
+                let nopts = convert::NdjsonOptions {
+                    per_address,
+                    updated_field,
+                };
+
+                convert::into_ndjson(&nopts, create_reader(input_path)?, &mut writer)?
+            } else {
+                let output_format = output_format.unwrap_or(if disable_pretty {
+                    OutputFormat::JSON
+                } else {
+                    OutputFormat::PrettyJSON
+                });
+
+                if normalized {
+                    convert::convert_normalized(&output_format, create_reader(input_path)?, &mut writer)?
+                } else {
+                    convert::convert(&output_format, create_reader(input_path)?, &mut writer)?
+                }
+            }
+        },
+
+        Command::Normalize => normalize::normalize(create_reader(input_path)?, &mut writer)?,
+
+        Command::Routes {
+            complement,
+            exclude_reserved,
+        } => {
+            let ropts = routes::RoutesOptions {
+                complement,
+                exclude_reserved,
+            };
+
+            routes::routes(&ropts, create_reader(input_path)?, &mut writer)?
+        },
 
         Command::Select {
             ipv4,
@@ -181,6 +663,7 @@ fn real_main() -> Result<(), failure::Error> {
             domain,
             wildcard_domain,
             url,
+            aggregate,
         } => {
             let sopts = select::SelectOptions {
                 ipv4,
@@ -188,16 +671,20 @@ fn real_main() -> Result<(), failure::Error> {
                 domain,
                 wildcard_domain,
                 url,
+
+                aggregate,
             };
             ensure!(
                 sopts.ipv4 || sopts.ipv4_network || sopts.domain || sopts.wildcard_domain || sopts.url,
                 "At least one selection should be specified"
             );
 
-            select::select(&sopts, reader, &mut writer)?
+            select::select(&sopts, create_reader(input_path)?, &mut writer)?
         },
 
-        Command::Updated => writeln!(writer, "{}", reader.get_timestamp())?,
+        Command::Updated => writeln!(writer, "{}", create_reader(input_path)?.get_timestamp())?,
+
+        Command::Schema => schema::schema(&mut writer)?,
 
         Command::Search {
             addresses,
@@ -205,7 +692,7 @@ fn real_main() -> Result<(), failure::Error> {
         } => {
             ensure!(!addresses.is_empty(), "At least one address should be specified");
 
-            search::search(&addresses, reader, &mut writer, &output_format)?
+            search::search(&addresses, create_reader(input_path)?, &mut writer, &output_format)?
         },
     }
     writer.flush()?;
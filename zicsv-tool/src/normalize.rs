@@ -0,0 +1,38 @@
+use std;
+
+use failure;
+
+use zicsv;
+
+use print_err;
+
+pub fn normalize<StreamWriter>(
+    mut reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    let mut n_errors = 0usize;
+    let mut records = Vec::new();
+
+    for record in reader.iter() {
+        match record {
+            Ok(record) => records.push(record),
+
+            Err(error) => {
+                n_errors += 1;
+                print_err::print_error(&error);
+            },
+        }
+    }
+
+    // Do not write incomplete list: it would look like a valid snapshot.
+    ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
+
+    let mut csv_writer = zicsv::Writer::from_writer(writer, reader.get_timestamp())?;
+    for record in zicsv::normalize(records) {
+        csv_writer.write_record(&record)?;
+    }
+    csv_writer.flush()
+}
@@ -0,0 +1,39 @@
+use std;
+
+use failure;
+
+use zicsv;
+
+use aggregate;
+
+pub struct RoutesOptions {
+    pub complement: bool,
+    pub exclude_reserved: bool,
+}
+
+pub fn routes<StreamWriter>(
+    options: &RoutesOptions,
+    reader: Box<zicsv::GenericReader>,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    // Do not print routes for incomplete list: with --complement this would route blocked addresses around tunnel.
+    let ipv4_addresses = aggregate::read_ipv4_addresses(reader)?;
+
+    let mut networks = if options.complement {
+        zicsv::complement_ipv4(&ipv4_addresses)
+    } else {
+        zicsv::aggregate_ipv4(&ipv4_addresses)
+    };
+    if options.exclude_reserved {
+        networks = zicsv::exclude_ipv4_networks(&networks, &zicsv::reserved_ipv4_networks());
+    }
+
+    for network in networks {
+        writeln!(writer, "{}", zicsv::Address::IPv4Network(network))?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,107 @@
+use std;
+
+use failure;
+
+/// JSON Schema of JSON output of "convert" (including "--normalized" layout) and "search".
+///
+/// Root schema describes the list, other types are available in "definitions".
+pub const SCHEMA: &str = include_str!("schema.json");
+
+pub fn schema<StreamWriter>(writer: &mut StreamWriter) -> Result<(), failure::Error>
+where
+    StreamWriter: std::io::Write,
+{
+    writer.write_all(SCHEMA.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+pub mod tests {
+    #[cfg(feature = "schema-tests")]
+    use std;
+
+    #[cfg(feature = "schema-tests")]
+    use jsonschema;
+    use serde_json;
+
+    #[cfg(feature = "schema-tests")]
+    use zicsv;
+
+    #[cfg(feature = "schema-tests")]
+    use convert;
+
+    /// Validator of one of types from "definitions".
+    #[cfg(feature = "schema-tests")]
+    pub fn validator(definition: &str) -> jsonschema::JSONSchema {
+        let mut schema: serde_json::Value = serde_json::from_str(super::SCHEMA).unwrap();
+        schema["$ref"] = serde_json::Value::String(format!("#/definitions/{}", definition));
+
+        jsonschema::JSONSchema::options()
+            .should_validate_formats(true)
+            .compile(&schema)
+            .unwrap()
+    }
+
+    #[cfg(feature = "schema-tests")]
+    pub fn assert_valid(definition: &str, value: &serde_json::Value) {
+        let validator = validator(definition);
+        if let Err(errors) = validator.validate(value) {
+            let errors: Vec<_> = errors.map(|error| error.to_string()).collect();
+            panic!("Invalid {}: {}", definition, errors.join("; "));
+        };
+    }
+
+    #[cfg(feature = "schema-tests")]
+    const LIST: &str = "\
+                        Updated: 2017-11-29 13:34:56 +0000\n\
+                        1.2.3.4;example.com;http://example.com/;Org 1;1-2-3;2017-01-02\n\
+                        1.2.3.0/24 | 4.3.2.1;*.example.org;;Org 2;4-5-6;2017-01-02\n\
+                        ;*;http://xn--e1aybc.org:8080/test | http://example.net/path?a=b;Org 1;7-8-9;2017-01-03\n\
+                        ";
+
+    #[cfg(feature = "schema-tests")]
+    fn convert(normalized: bool) -> serde_json::Value {
+        let reader = Box::new(zicsv::Reader::from_reader(std::io::Cursor::new(LIST.to_string())).unwrap());
+        let mut output = Vec::new();
+        if normalized {
+            convert::convert_normalized(&super::super::OutputFormat::JSON, reader, &mut output).unwrap();
+        } else {
+            convert::convert(&super::super::OutputFormat::JSON, reader, &mut output).unwrap();
+        }
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn schema_is_valid_json() {
+        let _: serde_json::Value = serde_json::from_str(super::SCHEMA).unwrap();
+    }
+
+    #[cfg(feature = "schema-tests")]
+    #[test]
+    fn convert_output_is_valid() {
+        let list = convert(false);
+        assert_valid("List", &list);
+
+        // Root schema describes the same.
+        let schema: serde_json::Value = serde_json::from_str(super::SCHEMA).unwrap();
+        assert!(jsonschema::JSONSchema::compile(&schema).unwrap().is_valid(&list));
+    }
+
+    #[cfg(feature = "schema-tests")]
+    #[test]
+    fn normalized_output_is_valid() {
+        assert_valid("NormalizedList", &convert(true));
+    }
+
+    #[cfg(feature = "schema-tests")]
+    #[test]
+    fn invalid_output() {
+        let mut list = convert(false);
+        list["records"][0]["addresses"][0] = serde_json::from_str("{\"IPv6\": \"::1\"}").unwrap();
+        assert!(!validator("List").is_valid(&list));
+
+        let mut list = convert(false);
+        list["records"][0]["document_date"] = serde_json::Value::String("02.01.2017".into());
+        assert!(!validator("List").is_valid(&list));
+    }
+}
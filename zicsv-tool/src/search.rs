@@ -2,7 +2,6 @@ use std;
 
 use failure;
 use serde;
-use serde_json;
 use trust_dns_proto;
 use trust_dns_resolver;
 use url;
@@ -10,6 +9,7 @@ use url;
 use zicsv;
 
 use print_err;
+use serialize;
 
 fn resolve_helper<T, F>(
     record_type: &str,
@@ -452,8 +452,7 @@ where
 
     match *output_format {
         super::OutputFormat::HumanReadable => print_human_readable(writer, &addresses)?,
-        super::OutputFormat::PrettyJSON => serde_json::to_writer_pretty(writer, &addresses)?,
-        super::OutputFormat::JSON => serde_json::to_writer(writer, &addresses)?,
+        ref output_format => serialize::serialize(output_format, &addresses, writer)?,
     }
 
     ensure!(
@@ -471,11 +470,19 @@ where
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "schema-tests")]
+    use std;
+
     use ipnet;
+    #[cfg(feature = "schema-tests")]
+    use serde_json;
     use trust_dns_resolver;
 
     use zicsv;
 
+    #[cfg(feature = "schema-tests")]
+    use schema;
+
     fn create_resolver() -> trust_dns_resolver::Resolver {
         // Empty configuration, no DNS servers.
         let conf = trust_dns_resolver::config::ResolverConfig::new();
@@ -767,4 +774,39 @@ mod tests {
             Some(super::MatchReason::URLInBlockedURL),
         );
     }
+
+    #[cfg(feature = "schema-tests")]
+    #[test]
+    fn results_match_schema() {
+        use zicsv::GenericReader;
+
+        let list = "Updated: 2017-11-29 13:34:56 +0000\n1.2.3.0/24;*.example.com;;Org 1;1-2-3;2017-01-02\n";
+        let mut reader = zicsv::Reader::from_reader(std::io::Cursor::new(list)).unwrap();
+        let record = std::rc::Rc::new(reader.iter().next().unwrap().unwrap());
+
+        let sub_addresses: Vec<zicsv::Address> = vec![
+            "http://test.example.com/".parse().unwrap(),
+            "test.example.com".parse().unwrap(),
+            "1.2.3.4".parse().unwrap(),
+        ];
+        let mut addresses = vec![
+            super::Address {
+                original_address: "http://test.example.com/",
+                addresses: sub_addresses
+                    .into_iter()
+                    .map(|address| super::SubAddressWithMatches {
+                        address,
+                        matches: Vec::new(),
+                    })
+                    .collect(),
+            },
+        ];
+        for address in &mut addresses {
+            super::find_matches(&record, address);
+        }
+        assert_eq!(addresses[0].addresses[1].matches.len(), 1);
+        assert_eq!(addresses[0].addresses[2].matches.len(), 1);
+
+        schema::tests::assert_valid("SearchResults", &serde_json::to_value(&addresses).unwrap());
+    }
 }
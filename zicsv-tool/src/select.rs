@@ -12,6 +12,8 @@ pub struct SelectOptions {
     pub domain: bool,
     pub wildcard_domain: bool,
     pub url: bool,
+
+    pub aggregate: bool,
 }
 
 pub fn select<StreamWriter>(
@@ -23,6 +25,7 @@ where
     StreamWriter: std::io::Write,
 {
     let mut n_errors = 0usize;
+    let mut ipv4_addresses = zicsv::Addresses::new();
 
     for record in reader.iter() {
         match record {
@@ -39,7 +42,13 @@ where
                 };
 
                 if selected {
-                    writeln!(writer, "{}", address)?;
+                    match *address {
+                        zicsv::Address::IPv4(_) | zicsv::Address::IPv4Network(_) if options.aggregate => {
+                            ipv4_addresses.push(address.clone())
+                        },
+
+                        _ => writeln!(writer, "{}", address)?,
+                    }
                 }
             },
 
@@ -50,6 +59,10 @@ where
         }
     }
 
+    for network in zicsv::aggregate_ipv4(&ipv4_addresses) {
+        writeln!(writer, "{}", zicsv::Address::IPv4Network(network))?;
+    }
+
     ensure!(n_errors == 0, "{} errors occur while reading list", n_errors);
     Ok(())
 }
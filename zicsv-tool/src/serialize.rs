@@ -0,0 +1,44 @@
+use std;
+
+use failure;
+#[cfg(feature = "msgpack")]
+use rmp_serde;
+use serde;
+#[cfg(feature = "cbor")]
+use serde_cbor;
+use serde_json;
+#[cfg(feature = "yaml")]
+use serde_yaml;
+#[cfg(feature = "toml")]
+use toml;
+
+/// Serialize value in one of machine-readable output formats.
+///
+/// TOML supports only tables at top level. Binary formats (CBOR and MessagePack) encode IPv4 addresses as arrays of
+/// four bytes.
+pub fn serialize<Value, StreamWriter>(
+    output_format: &super::OutputFormat,
+    value: &Value,
+    writer: &mut StreamWriter,
+) -> Result<(), failure::Error>
+where
+    Value: serde::Serialize,
+    StreamWriter: std::io::Write,
+{
+    match *output_format {
+        super::OutputFormat::PrettyJSON => serde_json::to_writer_pretty(writer, value)?,
+        super::OutputFormat::JSON => serde_json::to_writer(writer, value)?,
+        #[cfg(feature = "yaml")]
+        super::OutputFormat::YAML => serde_yaml::to_writer(writer, value)?,
+        #[cfg(feature = "toml")]
+        super::OutputFormat::TOML => writer.write_all(toml::to_string(value)?.as_bytes())?,
+        #[cfg(feature = "cbor")]
+        super::OutputFormat::CBOR => serde_cbor::to_writer(writer, value)?,
+        #[cfg(feature = "msgpack")]
+        super::OutputFormat::MessagePack => rmp_serde::encode::write_named(writer, value)?,
+
+        super::OutputFormat::HumanReadable => bail!("Output format \"{}\" is not supported", output_format),
+    }
+
+    Ok(())
+}
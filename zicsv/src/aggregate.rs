@@ -0,0 +1,553 @@
+use std;
+
+use ipnet;
+
+use types;
+
+/// Half-open range of IPv4 addresses: `[start, end)`. `u64` is used to represent the end of `255.255.255.255/32`.
+pub(crate) type Ipv4Range = (u64, u64);
+
+pub(crate) const IPV4_SPACE_END: u64 = 1 << 32;
+
+/// Special-purpose IPv4 networks which are not routed over the Internet (RFC 6890, RFC 5771, RFC 1112).
+const RESERVED_IPV4_NETWORKS: [([u8; 4], u8); 15] = [
+    ([0, 0, 0, 0], 8),
+    ([10, 0, 0, 0], 8),
+    ([100, 64, 0, 0], 10),
+    ([127, 0, 0, 0], 8),
+    ([169, 254, 0, 0], 16),
+    ([172, 16, 0, 0], 12),
+    ([192, 0, 0, 0], 24),
+    ([192, 0, 2, 0], 24),
+    ([192, 88, 99, 0], 24),
+    ([192, 168, 0, 0], 16),
+    ([198, 18, 0, 0], 15),
+    ([198, 51, 100, 0], 24),
+    ([203, 0, 113, 0], 24),
+    ([224, 0, 0, 0], 4),
+    ([240, 0, 0, 0], 4),
+];
+
+pub(crate) fn ipv4_network_range(network: &ipnet::Ipv4Net) -> Ipv4Range {
+    (
+        u64::from(u32::from(network.network())),
+        u64::from(u32::from(network.broadcast())) + 1,
+    )
+}
+
+pub(crate) fn ipv4_address_range(address: &types::Address) -> Option<Ipv4Range> {
+    match *address {
+        types::Address::IPv4(ipv4) => Some((u64::from(u32::from(ipv4)), u64::from(u32::from(ipv4)) + 1)),
+        types::Address::IPv4Network(ref network) => Some(ipv4_network_range(network)),
+
+        _ => None,
+    }
+}
+
+/// Sort ranges, merge overlapping and adjacent ones.
+pub(crate) fn merge_ipv4_ranges(mut ranges: Vec<Ipv4Range>) -> Vec<Ipv4Range> {
+    ranges.sort();
+
+    let mut merged: Vec<Ipv4Range> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        if let Some(last) = merged.last_mut() {
+            if start <= last.1 {
+                last.1 = std::cmp::max(last.1, end);
+                continue;
+            }
+        }
+        merged.push((start, end));
+    }
+
+    merged
+}
+
+/// Split range into minimal number of networks.
+pub(crate) fn ipv4_range_into_networks(range: Ipv4Range, networks: &mut Vec<ipnet::Ipv4Net>) {
+    let (mut start, end) = range;
+    while start < end {
+        // Largest network aligned on the current start which does not cross the end of range.
+        let mut prefix_len = 32 - std::cmp::min(start.trailing_zeros(), 32);
+        while (1 << (32 - prefix_len)) > end - start {
+            prefix_len += 1;
+        }
+
+        networks.push(
+            ipnet::Ipv4Net::new(std::net::Ipv4Addr::from(start as u32), prefix_len as u8)
+                .expect("Prefix length is at most 32"),
+        );
+        start += 1 << (32 - prefix_len);
+    }
+}
+
+/// Remove excluded ranges from ranges. Both should be sorted and merged.
+pub(crate) fn subtract_ipv4_ranges(ranges: &[Ipv4Range], excluded: &[Ipv4Range]) -> Vec<Ipv4Range> {
+    let mut result = Vec::new();
+    let mut excluded = excluded.iter().peekable();
+
+    for &(mut start, end) in ranges {
+        while start < end {
+            while excluded
+                .peek()
+                .map_or(false, |&&(_, excluded_end)| excluded_end <= start)
+            {
+                let _ = excluded.next();
+            }
+
+            match excluded.peek() {
+                Some(&&(excluded_start, excluded_end)) if excluded_start < end => {
+                    if start < excluded_start {
+                        result.push((start, excluded_start));
+                    }
+                    start = excluded_end;
+                },
+
+                _ => {
+                    result.push((start, end));
+                    start = end;
+                },
+            }
+        }
+    }
+
+    result
+}
+
+pub(crate) fn ipv4_networks_into_ranges(networks: &[ipnet::Ipv4Net]) -> Vec<Ipv4Range> {
+    merge_ipv4_ranges(networks.iter().map(ipv4_network_range).collect())
+}
+
+pub(crate) fn ipv4_ranges_into_networks(ranges: &[Ipv4Range]) -> Vec<ipnet::Ipv4Net> {
+    let mut networks = Vec::new();
+    for range in ranges {
+        ipv4_range_into_networks(*range, &mut networks);
+    }
+    networks
+}
+
+pub(crate) fn merged_ipv4_ranges<'a, Addresses>(addresses: Addresses) -> Vec<Ipv4Range>
+where
+    Addresses: IntoIterator<Item = &'a types::Address>,
+{
+    merge_ipv4_ranges(addresses.into_iter().filter_map(ipv4_address_range).collect())
+}
+
+/// Collapse blocked IPv4 addresses and networks into minimal sorted set of non-overlapping networks.
+///
+/// Addresses of other types are ignored.
+pub fn aggregate_ipv4<'a, Addresses>(addresses: Addresses) -> Vec<ipnet::Ipv4Net>
+where
+    Addresses: IntoIterator<Item = &'a types::Address>,
+{
+    ipv4_ranges_into_networks(&merged_ipv4_ranges(addresses))
+}
+
+/// Collapse all IPv4 address space except blocked IPv4 addresses and networks into minimal sorted set of networks.
+///
+/// Addresses of other types are ignored.
+pub fn complement_ipv4<'a, Addresses>(addresses: Addresses) -> Vec<ipnet::Ipv4Net>
+where
+    Addresses: IntoIterator<Item = &'a types::Address>,
+{
+    ipv4_ranges_into_networks(&subtract_ipv4_ranges(
+        &[(0, IPV4_SPACE_END)],
+        &merged_ipv4_ranges(addresses),
+    ))
+}
+
+/// Remove excluded networks from networks and collapse the rest into minimal sorted set of networks.
+pub fn exclude_ipv4_networks(networks: &[ipnet::Ipv4Net], excluded: &[ipnet::Ipv4Net]) -> Vec<ipnet::Ipv4Net> {
+    ipv4_ranges_into_networks(&subtract_ipv4_ranges(
+        &ipv4_networks_into_ranges(networks),
+        &ipv4_networks_into_ranges(excluded),
+    ))
+}
+
+/// Special-purpose IPv4 networks: private, loopback, link-local, documentation, multicast etc.
+pub fn reserved_ipv4_networks() -> Vec<ipnet::Ipv4Net> {
+    RESERVED_IPV4_NETWORKS
+        .iter()
+        .map(|&(octets, prefix_len)| {
+            ipnet::Ipv4Net::new(std::net::Ipv4Addr::from(octets), prefix_len)
+                .expect("Prefix lengths of reserved networks are valid")
+        })
+        .collect()
+}
+
+/// Result of lossy aggregation of IPv4 addresses and networks.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LossyIpv4Aggregation {
+    /// Sorted non-overlapping networks covering all blocked addresses.
+    pub networks: Vec<ipnet::Ipv4Net>,
+    /// Number of blocked IPv4 addresses.
+    pub n_blocked: u64,
+    /// Number of addresses covered by networks but not blocked.
+    pub n_overblocked: u64,
+}
+
+/// Smallest network containing both ranges.
+fn ipv4_common_supernet(left: Ipv4Range, right: Ipv4Range) -> Ipv4Range {
+    let first = std::cmp::min(left.0, right.0);
+    let last = std::cmp::max(left.1, right.1) - 1;
+    let prefix_len = ((first ^ last) as u32).leading_zeros();
+    let size = 1u64 << (32 - prefix_len);
+    let start = first & !(size - 1);
+    (start, start + size)
+}
+
+/// Merge candidate: supernet of two neighbouring networks and number of unblocked addresses it adds.
+type MergeCandidate = std::cmp::Reverse<(u64, Ipv4Range)>;
+
+/// Number of addresses which are added by merging all current networks inside supernet into it.
+///
+/// Addresses which are not blocked, but already covered by previously merged networks, are not counted.
+fn merge_cost(networks: &std::collections::BTreeMap<u64, (u64, u64)>, supernet: Ipv4Range) -> u64 {
+    let n_covered: u64 = networks
+        .range(supernet.0..supernet.1)
+        .map(|(&start, &(end, _))| end - start)
+        .sum();
+    supernet.1 - supernet.0 - n_covered
+}
+
+fn push_merge_candidate(
+    networks: &std::collections::BTreeMap<u64, (u64, u64)>,
+    left: Ipv4Range,
+    right: Ipv4Range,
+    candidates: &mut std::collections::BinaryHeap<MergeCandidate>,
+) {
+    let supernet = ipv4_common_supernet(left, right);
+    candidates.push(std::cmp::Reverse((merge_cost(networks, supernet), supernet)));
+}
+
+/// Collapse blocked IPv4 addresses and networks into at most `max_networks` networks.
+///
+/// Starts from the exact aggregation and greedily merges neighbouring networks into the smallest common supernet,
+/// every time choosing the merge which adds the least number of addresses that are not blocked. Zero `max_networks`
+/// is treated as one.
+pub fn aggregate_ipv4_lossy<'a, Addresses>(addresses: Addresses, max_networks: usize) -> LossyIpv4Aggregation
+where
+    Addresses: IntoIterator<Item = &'a types::Address>,
+{
+    let exact = ipv4_ranges_into_networks(&merged_ipv4_ranges(addresses));
+
+    // Start of network => (end of network, number of blocked addresses in it).
+    let mut networks = std::collections::BTreeMap::new();
+    for network in &exact {
+        let (start, end) = ipv4_network_range(network);
+        let _ = networks.insert(start, (end, end - start));
+    }
+    let n_blocked: u64 = networks.values().map(|&(_, n_blocked)| n_blocked).sum();
+
+    let mut candidates = std::collections::BinaryHeap::new();
+    for pair in exact.windows(2) {
+        let left = ipv4_network_range(&pair[0]);
+        let right = ipv4_network_range(&pair[1]);
+        push_merge_candidate(&networks, left, right, &mut candidates);
+    }
+
+    while networks.len() > std::cmp::max(max_networks, 1) {
+        let std::cmp::Reverse((cost, supernet)) = match candidates.pop() {
+            Some(candidate) => candidate,
+            None => break,
+        };
+
+        // Skip candidate if it is already covered by a previously merged wider network.
+        let covered = networks
+            .range(..supernet.0 + 1)
+            .next_back()
+            .map_or(false, |(&start, &(end, _))| start <= supernet.0 && end >= supernet.1);
+        if covered {
+            continue;
+        }
+
+        // Cost decreases when networks inside supernet are merged after candidate was added.
+        let current_cost = merge_cost(&networks, supernet);
+        if current_cost != cost {
+            candidates.push(std::cmp::Reverse((current_cost, supernet)));
+            continue;
+        }
+
+        let inner: Vec<u64> = networks.range(supernet.0..supernet.1).map(|(&start, _)| start).collect();
+        let mut n_merged_blocked = 0;
+        for start in inner {
+            n_merged_blocked += networks.remove(&start).map_or(0, |(_, n_blocked)| n_blocked);
+        }
+        let _ = networks.insert(supernet.0, (supernet.1, n_merged_blocked));
+
+        // Merged network has new neighbours.
+        let left = networks
+            .range(..supernet.0)
+            .next_back()
+            .map(|(&start, &(end, _))| (start, end));
+        let right = networks
+            .range(supernet.1..)
+            .next()
+            .map(|(&start, &(end, _))| (start, end));
+        if let Some(left) = left {
+            push_merge_candidate(&networks, left, supernet, &mut candidates);
+        }
+        if let Some(right) = right {
+            push_merge_candidate(&networks, supernet, right, &mut candidates);
+        }
+    }
+
+    let mut result = LossyIpv4Aggregation {
+        networks: Vec::with_capacity(networks.len()),
+        n_blocked,
+        n_overblocked: 0,
+    };
+    for (start, (end, n_network_blocked)) in networks {
+        ipv4_range_into_networks((start, end), &mut result.networks);
+        result.n_overblocked += end - start - n_network_blocked;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use ipnet;
+
+    use types;
+
+    fn aggregate(addresses: &[&str]) -> Vec<ipnet::Ipv4Net> {
+        let addresses: Vec<types::Address> = addresses.iter().map(|address| address.parse().unwrap()).collect();
+        super::aggregate_ipv4(&addresses)
+    }
+
+    fn networks(networks: &[&str]) -> Vec<ipnet::Ipv4Net> {
+        networks.iter().map(|network| network.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn aggregate_nothing() {
+        assert!(aggregate(&[]).is_empty());
+        assert!(aggregate(&["example.com", "*.example.com", "http://example.com/"]).is_empty());
+    }
+
+    #[test]
+    fn aggregate_addresses() {
+        assert_eq!(aggregate(&["1.2.3.4"]), networks(&["1.2.3.4/32"]));
+        assert_eq!(aggregate(&["1.2.3.4", "1.2.3.4"]), networks(&["1.2.3.4/32"]));
+        assert_eq!(aggregate(&["1.2.3.5", "1.2.3.4"]), networks(&["1.2.3.4/31"]));
+        assert_eq!(
+            aggregate(&["1.2.3.5", "1.2.3.6"]),
+            networks(&["1.2.3.5/32", "1.2.3.6/32"])
+        );
+        assert_eq!(
+            aggregate(&["1.2.3.4", "1.2.3.5", "1.2.3.6", "1.2.3.7", "1.2.3.8"]),
+            networks(&["1.2.3.4/30", "1.2.3.8/32"])
+        );
+    }
+
+    #[test]
+    fn aggregate_networks() {
+        assert_eq!(aggregate(&["1.2.3.4/24"]), networks(&["1.2.3.0/24"]));
+        assert_eq!(
+            aggregate(&["1.2.3.0/24", "1.2.2.0/24"]),
+            networks(&["1.2.2.0/23"])
+        );
+        assert_eq!(
+            aggregate(&["1.2.3.0/24", "1.2.4.0/24"]),
+            networks(&["1.2.3.0/24", "1.2.4.0/24"])
+        );
+        assert_eq!(
+            aggregate(&["1.2.0.0/16", "1.2.3.0/24", "1.2.3.4", "example.com"]),
+            networks(&["1.2.0.0/16"])
+        );
+        assert_eq!(
+            aggregate(&["1.2.3.0/25", "1.2.3.128", "1.2.3.129/32", "1.2.3.130/31", "1.2.3.132/30"]),
+            networks(&["1.2.3.0/25", "1.2.3.128/29"])
+        );
+        assert_eq!(aggregate(&["0.0.0.0/1", "128.0.0.0/1"]), networks(&["0.0.0.0/0"]));
+        assert_eq!(
+            aggregate(&["255.255.255.255", "255.255.255.254"]),
+            networks(&["255.255.255.254/31"])
+        );
+    }
+
+    #[test]
+    fn complement() {
+        let complement = |addresses: &[&str]| {
+            let addresses: Vec<types::Address> = addresses.iter().map(|address| address.parse().unwrap()).collect();
+            super::complement_ipv4(&addresses)
+        };
+
+        assert_eq!(complement(&[]), networks(&["0.0.0.0/0"]));
+        assert_eq!(complement(&["example.com"]), networks(&["0.0.0.0/0"]));
+        assert!(complement(&["0.0.0.0/0"]).is_empty());
+        assert_eq!(complement(&["0.0.0.0/1"]), networks(&["128.0.0.0/1"]));
+        assert_eq!(
+            complement(&["0.0.0.0", "255.255.255.255"]),
+            networks(&[
+                "0.0.0.1/32",
+                "0.0.0.2/31",
+                "0.0.0.4/30",
+                "0.0.0.8/29",
+                "0.0.0.16/28",
+                "0.0.0.32/27",
+                "0.0.0.64/26",
+                "0.0.0.128/25",
+                "0.0.1.0/24",
+                "0.0.2.0/23",
+                "0.0.4.0/22",
+                "0.0.8.0/21",
+                "0.0.16.0/20",
+                "0.0.32.0/19",
+                "0.0.64.0/18",
+                "0.0.128.0/17",
+                "0.1.0.0/16",
+                "0.2.0.0/15",
+                "0.4.0.0/14",
+                "0.8.0.0/13",
+                "0.16.0.0/12",
+                "0.32.0.0/11",
+                "0.64.0.0/10",
+                "0.128.0.0/9",
+                "1.0.0.0/8",
+                "2.0.0.0/7",
+                "4.0.0.0/6",
+                "8.0.0.0/5",
+                "16.0.0.0/4",
+                "32.0.0.0/3",
+                "64.0.0.0/2",
+                "128.0.0.0/2",
+                "192.0.0.0/3",
+                "224.0.0.0/4",
+                "240.0.0.0/5",
+                "248.0.0.0/6",
+                "252.0.0.0/7",
+                "254.0.0.0/8",
+                "255.0.0.0/9",
+                "255.128.0.0/10",
+                "255.192.0.0/11",
+                "255.224.0.0/12",
+                "255.240.0.0/13",
+                "255.248.0.0/14",
+                "255.252.0.0/15",
+                "255.254.0.0/16",
+                "255.255.0.0/17",
+                "255.255.128.0/18",
+                "255.255.192.0/19",
+                "255.255.224.0/20",
+                "255.255.240.0/21",
+                "255.255.248.0/22",
+                "255.255.252.0/23",
+                "255.255.254.0/24",
+                "255.255.255.0/25",
+                "255.255.255.128/26",
+                "255.255.255.192/27",
+                "255.255.255.224/28",
+                "255.255.255.240/29",
+                "255.255.255.248/30",
+                "255.255.255.252/31",
+                "255.255.255.254/32",
+            ])
+        );
+    }
+
+    #[test]
+    fn exclude_networks() {
+        assert!(super::exclude_ipv4_networks(&[], &networks(&["10.0.0.0/8"])).is_empty());
+        assert_eq!(
+            super::exclude_ipv4_networks(&networks(&["10.0.0.0/8"]), &[]),
+            networks(&["10.0.0.0/8"])
+        );
+        assert_eq!(
+            super::exclude_ipv4_networks(&networks(&["10.0.0.0/8", "11.0.0.0/8"]), &networks(&["10.0.0.0/7"])),
+            networks(&[])
+        );
+        assert_eq!(
+            super::exclude_ipv4_networks(
+                &networks(&["10.0.0.0/8", "12.0.0.0/8"]),
+                &networks(&["10.128.0.0/9", "12.0.0.0/9", "11.0.0.0/8"])
+            ),
+            networks(&["10.0.0.0/9", "12.128.0.0/9"])
+        );
+        assert_eq!(
+            super::exclude_ipv4_networks(&networks(&["0.0.0.0/0"]), &super::reserved_ipv4_networks())[..6].to_vec(),
+            networks(&["1.0.0.0/8", "2.0.0.0/7", "4.0.0.0/6", "8.0.0.0/7", "11.0.0.0/8", "12.0.0.0/6"])
+        );
+    }
+
+    fn aggregate_lossy(addresses: &[&str], max_networks: usize) -> super::LossyIpv4Aggregation {
+        let addresses: Vec<types::Address> = addresses.iter().map(|address| address.parse().unwrap()).collect();
+        super::aggregate_ipv4_lossy(&addresses, max_networks)
+    }
+
+    #[test]
+    fn aggregate_lossy_within_limit() {
+        assert_eq!(
+            aggregate_lossy(&[], 1),
+            super::LossyIpv4Aggregation {
+                networks: vec![],
+                n_blocked: 0,
+                n_overblocked: 0,
+            }
+        );
+
+        assert_eq!(
+            aggregate_lossy(&["1.2.3.4", "1.2.3.5", "1.2.3.8"], 2),
+            super::LossyIpv4Aggregation {
+                networks: networks(&["1.2.3.4/31", "1.2.3.8/32"]),
+                n_blocked: 3,
+                n_overblocked: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn aggregate_lossy_merges() {
+        assert_eq!(
+            aggregate_lossy(&["1.2.3.4", "1.2.3.7"], 1),
+            super::LossyIpv4Aggregation {
+                networks: networks(&["1.2.3.4/30"]),
+                n_blocked: 2,
+                n_overblocked: 2,
+            }
+        );
+
+        // Merging 1.2.3.0/32 with 1.2.3.2/32 is cheaper than merging 1.2.3.2/32 with 1.2.3.128/25.
+        assert_eq!(
+            aggregate_lossy(&["1.2.3.0", "1.2.3.2", "1.2.3.128/25"], 2),
+            super::LossyIpv4Aggregation {
+                networks: networks(&["1.2.3.0/30", "1.2.3.128/25"]),
+                n_blocked: 130,
+                n_overblocked: 2,
+            }
+        );
+
+        // Merged networks are merged further when needed.
+        assert_eq!(
+            aggregate_lossy(&["1.2.3.0", "1.2.3.2", "1.2.3.128/25"], 1),
+            super::LossyIpv4Aggregation {
+                networks: networks(&["1.2.3.0/24"]),
+                n_blocked: 130,
+                n_overblocked: 126,
+            }
+        );
+
+        // Addresses already covered by merged network are not counted again: merging 10.0.0.0/27 with 10.0.0.63
+        // adds 31 addresses, merging 10.1.0.0/28 with 10.1.0.63 adds 47.
+        assert_eq!(
+            aggregate_lossy(
+                &["10.0.0.0", "10.0.0.31", "10.0.0.63", "10.1.0.0/28", "10.1.0.63"],
+                3
+            ),
+            super::LossyIpv4Aggregation {
+                networks: networks(&["10.0.0.0/26", "10.1.0.0/28", "10.1.0.63/32"]),
+                n_blocked: 20,
+                n_overblocked: 61,
+            }
+        );
+
+        // Networks are merged only until limit is reached.
+        assert_eq!(
+            aggregate_lossy(&["10.0.0.1", "10.0.0.2", "10.0.0.4", "10.0.0.200"], 2),
+            super::LossyIpv4Aggregation {
+                networks: networks(&["10.0.0.0/29", "10.0.0.200/32"]),
+                n_blocked: 4,
+                n_overblocked: 5,
+            }
+        );
+    }
+}
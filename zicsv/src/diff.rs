@@ -0,0 +1,142 @@
+use std;
+
+use failure;
+
+use normalize;
+use reader;
+use types;
+
+/// Result of record-level comparison of two lists.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Diff {
+    /// Records present only in the old list.
+    pub removed: Vec<types::Record>,
+    /// Records present only in the new list.
+    pub added: Vec<types::Record>,
+    /// Records present in both lists.
+    pub unchanged: Vec<types::Record>,
+}
+
+fn read_normalized(list: &mut reader::GenericReader) -> Result<Vec<types::Record>, failure::Error> {
+    Ok(normalize::normalize(list.iter().collect::<Result<Vec<_>, _>>()?))
+}
+
+/// Compare records of two lists.
+///
+/// Both lists are normalized before comparison (see `normalize()`), so order of records and addresses does not
+/// matter. Record with changed addresses appears both in `removed` and `added`. Stops on the first reader error.
+pub fn diff(old: &mut reader::GenericReader, new: &mut reader::GenericReader) -> Result<Diff, failure::Error> {
+    let old = read_normalized(old).map_err(|error| error.context("Old list"))?;
+    let new = read_normalized(new).map_err(|error| error.context("New list"))?;
+
+    let old_set: std::collections::BTreeSet<&types::Record> = old.iter().collect();
+    let new_set: std::collections::BTreeSet<&types::Record> = new.iter().collect();
+
+    let removed = old.iter()
+        .filter(|record| !new_set.contains(record))
+        .cloned()
+        .collect();
+    let (unchanged, added) = new.iter().cloned().partition(|record| old_set.contains(record));
+
+    Ok(Diff {
+        removed,
+        added,
+        unchanged,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std;
+
+    use chrono;
+
+    use reader;
+    use types;
+
+    fn from_str(data: &str) -> reader::Reader<std::io::BufReader<std::io::Cursor<&str>>> {
+        reader::Reader::from_reader(std::io::Cursor::new(data)).unwrap()
+    }
+
+    fn record(addresses: &[&str], document_id: &str) -> types::Record {
+        types::Record {
+            addresses: addresses.iter().map(|address| address.parse().unwrap()).collect(),
+            organization: "org".into(),
+            document_id: document_id.into(),
+            document_date: chrono::NaiveDate::from_ymd(2017, 1, 2),
+
+            ..types::Record::default()
+        }
+    }
+
+    #[test]
+    fn diff_lists() {
+        let mut old = from_str(
+            "\
+             Updated: 2017-11-29 12:34:56 -0100\n\
+             1.2.3.4;;;org;1;2017-01-02\n\
+             4.3.2.1;example.com;;org;2;2017-01-02\n\
+             ;example.org;;org;3;2017-01-02\n\
+             ",
+        );
+        let mut new = from_str(
+            "\
+             Updated: 2017-11-30 12:34:56 -0100\n\
+             ;example.org;;org;3;2017-01-02\n\
+             4.3.2.1;example.com|example.net;;org;2;2017-01-02\n\
+             1.2.3.0/24;;;org;4;2017-01-02\n\
+             ",
+        );
+
+        assert_eq!(
+            super::diff(&mut old, &mut new).unwrap(),
+            super::Diff {
+                removed: vec![record(&["1.2.3.4"], "1"), record(&["4.3.2.1", "example.com"], "2")],
+                added: vec![
+                    record(&["4.3.2.1", "example.com", "example.net"], "2"),
+                    record(&["1.2.3.0/24"], "4"),
+                ],
+                unchanged: vec![record(&["example.org"], "3")],
+            }
+        );
+    }
+
+    #[test]
+    fn diff_same_lists() {
+        let mut old = from_str(
+            "\
+             Updated: 2017-11-29 12:34:56 -0100\n\
+             1.2.3.4|4.3.2.1;;;org;1;2017-01-02\n\
+             ",
+        );
+        let mut new = from_str(
+            "\
+             Updated: 2017-11-29 12:34:56 -0100\n\
+             4.3.2.1;;;org;1;2017-01-02\n\
+             1.2.3.4;;;org;1;2017-01-02\n\
+             ",
+        );
+
+        let diff = super::diff(&mut old, &mut new).unwrap();
+        assert!(diff.removed.is_empty());
+        assert!(diff.added.is_empty());
+        assert_eq!(diff.unchanged, vec![record(&["1.2.3.4", "4.3.2.1"], "1")]);
+    }
+
+    #[test]
+    fn diff_invalid_list() {
+        let mut old = from_str(
+            "\
+             Updated: 2017-11-29 12:34:56 -0100\n\
+             invalid;;;org;1;2017-01-02\n\
+             ",
+        );
+        let mut new = from_str(
+            "\
+             Updated: 2017-11-29 12:34:56 -0100\n\
+             ",
+        );
+
+        assert!(super::diff(&mut old, &mut new).is_err());
+    }
+}
@@ -48,8 +48,20 @@ extern crate url_serde;
 #[cfg(feature = "serialization")]
 mod ipnet_serde;
 
+mod aggregate;
+pub use aggregate::*;
+
+mod diff;
+pub use diff::*;
+
+mod normalize;
+pub use normalize::*;
+
 mod reader;
 pub use reader::*;
 
 mod types;
 pub use types::*;
+
+mod writer;
+pub use writer::*;
@@ -0,0 +1,112 @@
+use std;
+
+use types;
+
+impl types::Record {
+    /// Sort addresses and remove duplicates.
+    pub fn normalize(&mut self) {
+        self.addresses.sort();
+        self.addresses.dedup();
+    }
+}
+
+/// Bring records into canonical form.
+///
+/// Records with identical organization, document ID and document date are merged into one. Addresses inside each
+/// record are sorted and deduplicated. Resulting records are sorted by organization, document ID and document date.
+pub fn normalize<Records>(records: Records) -> Vec<types::Record>
+where
+    Records: IntoIterator<Item = types::Record>,
+{
+    let mut merged = std::collections::BTreeMap::new();
+    for record in records {
+        let types::Record {
+            addresses,
+            organization,
+            document_id,
+            document_date,
+            ..
+        } = record;
+
+        merged
+            .entry((organization, document_id, document_date))
+            .or_insert_with(types::Addresses::new)
+            .extend(addresses);
+    }
+
+    merged
+        .into_iter()
+        .map(|((organization, document_id, document_date), addresses)| {
+            let mut record = types::Record {
+                addresses,
+                organization,
+                document_id,
+                document_date,
+
+                __may_be_extended: (),
+            };
+            record.normalize();
+            record
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono;
+
+    use types;
+
+    fn record(addresses: &[&str], organization: &str, document_id: &str, day: u32) -> types::Record {
+        types::Record {
+            addresses: addresses.iter().map(|address| address.parse().unwrap()).collect(),
+            organization: organization.into(),
+            document_id: document_id.into(),
+            document_date: chrono::NaiveDate::from_ymd(2017, 1, day),
+
+            ..types::Record::default()
+        }
+    }
+
+    #[test]
+    fn normalize_record() {
+        let mut normalized = record(
+            &["http://example.com/", "example.com", "1.2.3.4", "1.2.3.0/24", "1.2.3.4", "*.example.com"],
+            "org",
+            "id",
+            1,
+        );
+        normalized.normalize();
+        assert_eq!(
+            normalized,
+            record(
+                &["1.2.3.4", "1.2.3.0/24", "example.com", "*.example.com", "http://example.com/"],
+                "org",
+                "id",
+                1,
+            )
+        );
+    }
+
+    #[test]
+    fn normalize_records() {
+        assert!(super::normalize(vec![]).is_empty());
+
+        let records = vec![
+            record(&["example.org", "1.2.3.4"], "org2", "id1", 1),
+            record(&["example.com"], "org1", "id2", 1),
+            record(&["1.2.3.4", "example.com"], "org1", "id1", 2),
+            record(&["example.com", "4.3.2.1"], "org1", "id1", 1),
+            record(&["example.com"], "org1", "id1", 1),
+        ];
+        assert_eq!(
+            super::normalize(records),
+            vec![
+                record(&["4.3.2.1", "example.com"], "org1", "id1", 1),
+                record(&["1.2.3.4", "example.com"], "org1", "id1", 2),
+                record(&["example.com"], "org1", "id2", 1),
+                record(&["1.2.3.4", "example.org"], "org2", "id1", 1),
+            ]
+        );
+    }
+}
@@ -17,6 +17,9 @@ pub trait GenericReader {
     /// Iterate over records using generic iterator.
     // TODO: Change to into_iter() which will consume self?
     fn iter<'a>(&'a mut self) -> Box<Iterator<Item = Result<types::Record, failure::Error>> + 'a>;
+
+    /// Iterate over records with numbers of lines where they start, the same numbers as in error messages.
+    fn iter_numbered<'a>(&'a mut self) -> Box<Iterator<Item = (u64, Result<types::Record, failure::Error>)> + 'a>;
 }
 
 /// Reader bound to specific type of IO stream.
@@ -96,9 +99,16 @@ where
     fn iter<'a>(&'a mut self) -> Box<Iterator<Item = Result<types::Record, failure::Error>> + 'a> {
         Box::new(Records {
             csv_records: self.csv_reader.byte_records(),
-            line_n: 1,
+            line_n: 2,
         })
     }
+
+    fn iter_numbered<'a>(&'a mut self) -> Box<Iterator<Item = (u64, Result<types::Record, failure::Error>)> + 'a> {
+        Box::new(NumberedRecords(Records {
+            csv_records: self.csv_reader.byte_records(),
+            line_n: 2,
+        }))
+    }
 }
 
 /// Iterator over the list records containing information abould blocked addresses.
@@ -107,13 +117,46 @@ where
     StreamReader: std::io::BufRead,
 {
     csv_records: csv::ByteRecords<'a, StreamReader>,
+    /// Number of line where the next record starts.
     line_n: u64,
 }
 
+/// Iterator over the list records with numbers of lines where they start.
+pub struct NumberedRecords<'a, StreamReader: 'a>(Records<'a, StreamReader>)
+where
+    StreamReader: std::io::BufRead;
+
 impl<'a, StreamReader: 'a> Records<'a, StreamReader>
 where
     StreamReader: std::io::BufRead,
 {
+    /// Read next record and number of line where it starts.
+    fn next_numbered(&mut self) -> Option<(u64, Result<types::Record, failure::Error>)> {
+        let csv_result = self.csv_records.next()?;
+
+        let line_n = self.line_n;
+        self.line_n += 1;
+
+        let result = csv_result
+            .map_err(|csv_err| csv_err.into())
+            .and_then(|raw_record| {
+                // Quoted fields may contain line breaks, the next record starts on a later line.
+                self.line_n += Self::count_line_breaks(&raw_record);
+                Self::str_rec_from_cp1251(&raw_record)
+            })
+            .and_then(|str_record| Self::parse_record(&str_record))
+            .map_err(|error| error.context(format!("Line number: {}", line_n)).into());
+
+        Some((line_n, result))
+    }
+
+    fn count_line_breaks(raw_vec: &[Vec<u8>]) -> u64 {
+        raw_vec
+            .iter()
+            .map(|raw| raw.iter().filter(|&&byte| byte == b'\n').count() as u64)
+            .sum()
+    }
+
     fn str_from_cp1251(raw: &[u8]) -> Result<String, failure::Error> {
         use encoding::Encoding;
 
@@ -208,15 +251,18 @@ where
     type Item = Result<types::Record, failure::Error>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        self.line_n += 1;
+        self.next_numbered().map(|(_, result)| result)
+    }
+}
 
-        self.csv_records.next().map(|csv_result| -> Self::Item {
-            csv_result
-                .map_err(|csv_err| csv_err.into())
-                .and_then(|raw_record| Self::str_rec_from_cp1251(&raw_record))
-                .and_then(|str_record| Self::parse_record(&str_record))
-                .map_err(|error| error.context(format!("Line number: {}", self.line_n)).into())
-        })
+impl<'a, StreamReader: 'a> Iterator for NumberedRecords<'a, StreamReader>
+where
+    StreamReader: std::io::BufRead,
+{
+    type Item = (u64, Result<types::Record, failure::Error>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_numbered()
     }
 }
 
@@ -510,4 +556,27 @@ mod tests {
         let record = reader.iter().next().unwrap();
         assert!(record.is_err());
     }
+
+    #[test]
+    fn iter_numbered() {
+        use reader::GenericReader;
+
+        let mut reader = from_str(
+            "\
+             Updated: 2017-11-29 12:34:56 -0100\n\
+             ;;;org;1;2017-01-02\n\
+             ;;;\"multi-line\norg\";2;2017-01-02\n\
+             ;;;org;3;invalid\n\
+             ;;;org;4;2017-01-02\n\
+             ",
+        ).unwrap();
+        let records: Vec<_> = reader.iter_numbered().collect();
+        assert_eq!(records.len(), 4);
+
+        let line_numbers: Vec<_> = records.iter().map(|&(line_n, _)| line_n).collect();
+        assert_eq!(line_numbers, vec![2, 3, 5, 6]);
+
+        let error = records[2].1.as_ref().unwrap_err();
+        assert!(error.causes().any(|cause| cause.to_string() == "Line number: 5"));
+    }
 }
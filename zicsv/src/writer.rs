@@ -0,0 +1,207 @@
+use std;
+
+use csv;
+use encoding;
+use failure;
+
+use types;
+
+/// Writer of lists in the same CSV format as used by Zapret-Info.
+pub struct Writer<StreamWriter>
+where
+    StreamWriter: std::io::Write,
+{
+    csv_writer: csv::Writer<StreamWriter>,
+}
+
+impl<StreamWriter> Writer<StreamWriter>
+where
+    StreamWriter: std::io::Write,
+{
+    fn write_update_datetime(writer: &mut StreamWriter, updated: &types::DateTime) -> Result<(), failure::Error> {
+        writeln!(writer, "Updated: {} +0000", updated.format("%Y-%m-%d %H:%M:%S"))?;
+        Ok(())
+    }
+
+    /// Write list header with date of last update into writer and use it for records.
+    pub fn from_writer(mut writer: StreamWriter, updated: &types::DateTime) -> Result<Self, failure::Error> {
+        Self::write_update_datetime(&mut writer, updated)?;
+
+        Ok(Self {
+            csv_writer: csv::Writer::from_writer(writer)
+                .delimiter(b';')
+                .record_terminator(csv::RecordTerminator::Any(b'\n')),
+        })
+    }
+
+    fn str_into_cp1251(value: &str) -> Result<Vec<u8>, failure::Error> {
+        use encoding::Encoding;
+
+        encoding::all::WINDOWS_1251
+            .encode(value, encoding::EncoderTrap::Strict)
+            .map_err(|error| format_err!("Unable to encode string into CP1251 ({}): \"{}\"", error, value))
+    }
+
+    fn join_addresses<FilterFn>(addresses: &[types::Address], filter: FilterFn) -> String
+    where
+        FilterFn: Fn(&types::Address) -> bool,
+    {
+        // Reader also accepts "|" without spaces for IPv4 addresses and domain names, but URLs are always delimited
+        // by " | ".
+        addresses
+            .iter()
+            .filter(|address| filter(address))
+            .map(String::from)
+            .collect::<Vec<_>>()
+            .join(" | ")
+    }
+
+    /// Write one record.
+    pub fn write_record(&mut self, record: &types::Record) -> Result<(), failure::Error> {
+        let ipv4_addresses = Self::join_addresses(&record.addresses, |address| match *address {
+            types::Address::IPv4(_) | types::Address::IPv4Network(_) => true,
+            _ => false,
+        });
+        let domain_names = Self::join_addresses(&record.addresses, |address| match *address {
+            types::Address::DomainName(_) | types::Address::WildcardDomainName(_) => true,
+            _ => false,
+        });
+        let urls = Self::join_addresses(&record.addresses, |address| match *address {
+            types::Address::URL(_) => true,
+            _ => false,
+        });
+
+        let fields = vec![
+            Self::str_into_cp1251(&ipv4_addresses)?,
+            Self::str_into_cp1251(&domain_names)?,
+            Self::str_into_cp1251(&urls)?,
+            Self::str_into_cp1251(&record.organization)?,
+            Self::str_into_cp1251(&record.document_id)?,
+            Self::str_into_cp1251(&format!("{}", record.document_date.format("%Y-%m-%d")))?,
+        ];
+
+        self.csv_writer.write(fields.into_iter())?;
+        Ok(())
+    }
+
+    /// Flush underlying writer.
+    pub fn flush(&mut self) -> Result<(), failure::Error> {
+        self.csv_writer.flush()?;
+        Ok(())
+    }
+}
+
+impl Writer<std::io::BufWriter<std::fs::File>> {
+    fn from_file_no_context<Path: AsRef<std::path::Path>>(
+        path: Path,
+        updated: &types::DateTime,
+    ) -> Result<Self, failure::Error> {
+        Self::from_writer(std::io::BufWriter::new(std::fs::File::create(path)?), updated)
+    }
+
+    /// Create file specified by path and write list into it.
+    pub fn from_file<Path: AsRef<std::path::Path>>(
+        path: Path,
+        updated: &types::DateTime,
+    ) -> Result<Self, failure::Error> {
+        let path_str = format!("{}", path.as_ref().to_string_lossy());
+        Self::from_file_no_context(path, updated)
+            .map_err(|error| error.context(format!("File: \"{}\"", path_str)).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std;
+
+    use chrono;
+
+    use reader;
+    use types;
+
+    fn write_records(records: &[types::Record]) -> Vec<u8> {
+        let mut output = Vec::new();
+        {
+            let updated = chrono::NaiveDate::from_ymd(2017, 11, 29).and_hms(13, 34, 56);
+            let mut writer = super::Writer::from_writer(&mut output, &updated).unwrap();
+            for record in records {
+                writer.write_record(record).unwrap();
+            }
+            writer.flush().unwrap();
+        }
+        output
+    }
+
+    #[test]
+    fn write_no_records() {
+        assert_eq!(
+            String::from_utf8(write_records(&[])).unwrap(),
+            "Updated: 2017-11-29 13:34:56 +0000\n"
+        );
+    }
+
+    #[test]
+    fn write_records_into_csv() {
+        let records = vec![
+            types::Record {
+                document_date: chrono::NaiveDate::from_ymd(2017, 1, 2),
+
+                ..types::Record::default()
+            },
+            types::Record {
+                addresses: vec![
+                    types::Address::IPv4("1.2.3.4".parse().unwrap()),
+                    types::Address::IPv4Network("1.2.3.0/24".parse().unwrap()),
+                    types::Address::DomainName("example.com".into()),
+                    types::Address::WildcardDomainName("*.example.com".into()),
+                    types::Address::URL("http://example.com?test=x|y".parse().unwrap()),
+                    types::Address::URL("http://example.com?test=z".parse().unwrap()),
+                ],
+                organization: "org;string".into(),
+                document_id: "id string".into(),
+                document_date: chrono::NaiveDate::from_ymd(2017, 1, 2),
+
+                ..types::Record::default()
+            },
+        ];
+
+        assert_eq!(
+            String::from_utf8(write_records(&records)).unwrap(),
+            "\
+             Updated: 2017-11-29 13:34:56 +0000\n\
+             ;;;;;2017-01-02\n\
+             1.2.3.4 | 1.2.3.0/24;example.com | *.example.com;\
+             http://example.com/?test=x|y | http://example.com/?test=z;\"org;string\";id string;2017-01-02\n\
+             "
+        );
+    }
+
+    #[test]
+    fn write_and_read() {
+        use reader::GenericReader;
+
+        let records = vec![
+            types::Record {
+                addresses: vec![
+                    types::Address::IPv4("1.2.3.4".parse().unwrap()),
+                    types::Address::DomainName("xn--e1aybc.org".into()),
+                    types::Address::URL("http://xn--e1aybc.org/test".parse().unwrap()),
+                ],
+                organization: "\u{41c}\u{412}\u{414}".into(),
+                document_id: "27-31-2018/\u{418}\u{434}2971-18".into(),
+                document_date: chrono::NaiveDate::from_ymd(2018, 4, 16),
+
+                ..types::Record::default()
+            },
+        ];
+
+        let output = write_records(&records);
+        let mut reader = reader::Reader::from_reader(std::io::Cursor::new(output)).unwrap();
+        assert_eq!(
+            *reader.get_timestamp(),
+            chrono::NaiveDate::from_ymd(2017, 11, 29).and_hms(13, 34, 56)
+        );
+        let read_records: Vec<_> = reader.iter().map(Result::unwrap).collect();
+        assert_eq!(read_records, records);
+    }
+}